@@ -24,6 +24,26 @@ impl JdkVersion {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum FindingCategory {
+    MissingClass,
+    MissingMethod,
+    MissingField,
+    #[value(name = "class-interface-mismatch")]
+    KindMismatch,
+    StaticMismatch,
+    IllegalAccess,
+    UnimplementedMethod,
+}
+
 #[derive(Parser, Debug)]
 #[cfg_attr(
     feature = "embedded_classinfo",
@@ -35,7 +55,7 @@ impl JdkVersion {
 )]
 #[cfg_attr(not(feature = "embedded_classinfo"), command(version, about))]
 pub struct Args {
-    ///Classpath of JARs to be checked.
+    ///Classpath of JARs, class directories and class files to be checked.
     pub classpath: String,
     ///Java version to check
     #[cfg(feature = "embedded_classinfo")]
@@ -47,11 +67,60 @@ pub struct Args {
     pub jdk_classinfo: Option<String>,
     ///A file listing the available classes and methods of the relevant JDK.
     #[cfg(not(feature = "embedded_classinfo"))]
-    pub jdk_classinfo: String,
+    #[arg(required_unless_present_any = ["verify_signatures", "api_diff"])]
+    pub jdk_classinfo: Option<String>,
+    ///The Java release used to select the classes of multi-release JARs. Defaults to the Java version to check.
+    #[cfg(feature = "embedded_classinfo")]
+    #[arg(long)]
+    pub release: Option<u16>,
+    ///The Java release used to select the classes of multi-release JARs. Only the base classes are used if not set.
+    #[cfg(not(feature = "embedded_classinfo"))]
+    #[arg(long)]
+    pub release: Option<u16>,
+    ///Check the base classes and every release variant of multi-release JARs separately.
+    #[arg(long, conflicts_with = "release")]
+    pub all_releases: bool,
     ///The number of threads to use.
     #[arg(short, long, default_value_t = 1usize)]
     pub threads: usize,
     ///The output file path. Prints to stdout if not set.
     #[arg(short, long)]
     pub output_file: Option<String>,
+    ///The output format.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    ///Also check the JARs listed in the Class-Path manifest attribute of each JAR, transitively.
+    #[arg(long)]
+    pub manifest_classpath: bool,
+    ///Skip JARs and classes that cannot be read and list them in the report instead of aborting.
+    #[arg(long)]
+    pub keep_going: bool,
+    ///Group the findings by the required class instead of the consuming class, with the number of consumers.
+    #[arg(long, conflicts_with_all = ["before", "verify_signatures", "api_diff"])]
+    pub inverted: bool,
+    ///A JSON file of accepted findings to leave out of the report.
+    #[arg(long)]
+    pub suppressions: Option<String>,
+    ///A previous report in the JSON or text format. Only findings added or resolved since are reported.
+    #[arg(long)]
+    pub baseline: Option<String>,
+    ///The class path before an upgrade. Only findings added or resolved by the upgrade are reported.
+    #[arg(long, conflicts_with = "baseline")]
+    pub before: Option<String>,
+    ///The finding categories counted for the exit code. All categories if not set.
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub fail_on: Vec<FindingCategory>,
+    ///The number of findings tolerated before exiting with code 1.
+    #[arg(long, default_value_t = 0usize)]
+    pub max_findings: usize,
+    ///Verify the JAR signatures and manifest digests instead of checking dependencies.
+    #[arg(long)]
+    pub verify_signatures: bool,
+    ///Compare the public API of CLASSPATH with the old version given here instead of checking dependencies.
+    #[arg(
+        long,
+        value_name = "OLD_CLASSPATH",
+        conflicts_with = "verify_signatures"
+    )]
+    pub api_diff: Option<String>,
 }
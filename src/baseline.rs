@@ -0,0 +1,382 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use log::{info, warn};
+use reference_checker::{ClassRequirements, DependencyStatus, FindingKind};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, BaselineError};
+
+/// A single unmet dependency. A missing class or a class/interface mismatch has no member,
+/// while every missing method and field of a found class is a finding of its own.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Finding {
+    pub consumer: String,
+    pub dependency: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    //None for the classes of a text report, which does not tell a missing class from a
+    //class/interface mismatch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<FindingKind>,
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.member {
+            Some(member) => write!(f, "{}: {}.{}", self.consumer, self.dependency, member),
+            None => write!(f, "{}: {}", self.consumer, self.dependency),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonDocument {
+    schema_version: u32,
+    reports: Vec<JsonReport>,
+}
+
+#[derive(Deserialize)]
+struct JsonReport {
+    release: Option<u16>,
+    results: Vec<JsonResult>,
+}
+
+#[derive(Deserialize)]
+struct JsonResult {
+    name: String,
+    dependencies: Vec<JsonDependency>,
+}
+
+#[derive(Deserialize)]
+struct JsonDependency {
+    name: String,
+    status: DependencyStatus,
+    methods: Vec<String>,
+    fields: Vec<String>,
+    //missing in reports written before static mismatches were checked
+    #[serde(default)]
+    static_mismatches: Vec<JsonStaticMismatch>,
+    #[serde(default)]
+    inaccessible_members: Vec<JsonInaccessibleMember>,
+    #[serde(default)]
+    unimplemented_methods: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct JsonStaticMismatch {
+    method: String,
+}
+
+#[derive(Deserialize)]
+struct JsonInaccessibleMember {
+    member: String,
+}
+
+/// The kinds of the member findings of a found class, in the order `dependency_findings`
+/// expects their members.
+const MEMBER_KINDS: [FindingKind; 5] = [
+    FindingKind::MissingMethod,
+    FindingKind::MissingField,
+    FindingKind::StaticMismatch,
+    FindingKind::IllegalAccess,
+    FindingKind::UnimplementedMethod,
+];
+
+/// The findings of an unmet dependency with their kind. A class that is missing, inaccessible
+/// or of the other kind is a single finding without member, a found class has a finding for
+/// each of its `members`, listed in the order of `MEMBER_KINDS`.
+fn dependency_findings(
+    status: DependencyStatus,
+    members: [Vec<&str>; 5],
+) -> Vec<(FindingKind, Option<&str>)> {
+    match status {
+        DependencyStatus::Missing => vec![(FindingKind::MissingClass, None)],
+        DependencyStatus::KindMismatch => vec![(FindingKind::KindMismatch, None)],
+        DependencyStatus::Inaccessible => vec![(FindingKind::IllegalAccess, None)],
+        DependencyStatus::ApiMismatch => MEMBER_KINDS
+            .into_iter()
+            .zip(members)
+            .flat_map(|(kind, members)| members.into_iter().map(move |m| (kind, Some(m))))
+            .collect(),
+    }
+}
+
+/// The findings of a previous report, by release.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    reports: BTreeMap<Option<u16>, BTreeSet<Finding>>,
+}
+
+/// The difference between the baseline and one check.
+#[derive(Debug, Serialize)]
+pub struct Diff<'a> {
+    //findings of the check that are in the baseline as well
+    pub known: usize,
+    //findings of the baseline that are gone
+    pub resolved: Vec<&'a Finding>,
+}
+
+impl Baseline {
+    /// Reads a report written in the JSON or text format.
+    pub fn read(path: &str) -> Result<Self, error::Error> {
+        info!("Reading baseline from {path}");
+        let content = std::fs::read_to_string(path)?;
+        if content.trim_start().starts_with('{') {
+            Self::from_json(&content)
+        } else {
+            Self::from_text(&content)
+        }
+    }
+
+    fn from_json(content: &str) -> Result<Self, error::Error> {
+        let document: JsonDocument = serde_json::from_str(content)?;
+        if !(1..=crate::json::SCHEMA_VERSION).contains(&document.schema_version) {
+            return Err(BaselineError::UnsupportedSchema(document.schema_version).into());
+        }
+        fn strs(list: &[String]) -> Vec<&str> {
+            list.iter().map(String::as_str).collect()
+        }
+        let mut baseline = Baseline::default();
+        for report in document.reports {
+            let findings = baseline.reports.entry(report.release).or_default();
+            for result in report.results {
+                for dependency in result.dependencies {
+                    let members = [
+                        strs(&dependency.methods),
+                        strs(&dependency.fields),
+                        dependency
+                            .static_mismatches
+                            .iter()
+                            .map(|m| m.method.as_str())
+                            .collect(),
+                        dependency
+                            .inaccessible_members
+                            .iter()
+                            .map(|m| m.member.as_str())
+                            .collect(),
+                        strs(&dependency.unimplemented_methods),
+                    ];
+                    for (kind, member) in dependency_findings(dependency.status, members) {
+                        findings.insert(Finding {
+                            consumer: result.name.clone(),
+                            dependency: dependency.name.clone(),
+                            member: member.map(str::to_owned),
+                            category: Some(kind),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(baseline)
+    }
+
+    /// The findings of another check.
+    pub fn from_requirements(release: Option<u16>, requirements: &[ClassRequirements<'_>]) -> Self {
+        let mut baseline = Baseline::default();
+        baseline.reports.insert(release, BTreeSet::new());
+        for r in requirements {
+            for dependency in r.dependencies() {
+                let members = [
+                    dependency.methods.clone(),
+                    dependency.fields.clone(),
+                    dependency
+                        .static_mismatches
+                        .iter()
+                        .map(|m| m.method)
+                        .collect(),
+                    dependency
+                        .inaccessible_members
+                        .iter()
+                        .map(|m| m.member)
+                        .collect(),
+                    dependency.unimplemented_methods.clone(),
+                ];
+                for (kind, member) in dependency_findings(dependency.status, members) {
+                    baseline.add(release, r.name(), dependency.name, member, Some(kind));
+                }
+            }
+        }
+        baseline
+    }
+
+    /// Parses the text format, ignoring every section but the requirements.
+    fn from_text(content: &str) -> Result<Self, error::Error> {
+        let mut baseline = Baseline::default();
+        let mut release = None;
+        let mut consumer: Option<&str> = None;
+        //the required class, and whether it is found but lacks members
+        let mut dependency: Option<(&str, bool)> = None;
+        for (i, line) in content.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("\t\t") {
+                let member = match name.split_once(' ') {
+                    Some(("Method", member)) => Some((member, FindingKind::MissingMethod)),
+                    Some(("Field", member)) => Some((member, FindingKind::MissingField)),
+                    _ => None,
+                };
+                //static mismatches, inaccessible members and unimplemented methods are followed by an
+                //explanation
+                let member = member.map(|(member, missing)| match member.split_once(' ') {
+                    None => (member, missing),
+                    Some((member, "is not implemented")) => {
+                        (member, FindingKind::UnimplementedMethod)
+                    }
+                    Some((member, explanation)) if explanation.contains("static") => {
+                        (member, FindingKind::StaticMismatch)
+                    }
+                    Some((member, _)) => (member, FindingKind::IllegalAccess),
+                });
+                match (consumer, dependency, member) {
+                    (Some(consumer), Some((dependency, true)), Some((member, kind))) => {
+                        baseline.add(release, consumer, dependency, Some(member), Some(kind))
+                    }
+                    (Some(_), Some((_, false)), Some(_)) => {}
+                    (None, _, _) => {}
+                    _ => return Err(BaselineError::InvalidLine(i + 1).into()),
+                }
+            } else if let Some(name) = line.strip_prefix('\t') {
+                dependency = None;
+                let Some(consumer) = consumer else {
+                    continue;
+                };
+                let name = name
+                    .strip_prefix("Class ")
+                    .or_else(|| name.strip_prefix("Interface "))
+                    .ok_or(BaselineError::InvalidLine(i + 1))?;
+                let name = name.split(" (").next().unwrap_or(name);
+                let api_mismatch = line.contains(" (API mismatch)");
+                if line.contains(" (inaccessible)") {
+                    baseline.add(
+                        release,
+                        consumer,
+                        name,
+                        None,
+                        Some(FindingKind::IllegalAccess),
+                    );
+                } else if !api_mismatch {
+                    baseline.add(release, consumer, name, None, None);
+                }
+                dependency = Some((name, api_mismatch));
+            } else {
+                dependency = None;
+                consumer = None;
+                if line == "Release base:" {
+                    release = None;
+                } else if let Some(number) = line
+                    .strip_prefix("Release ")
+                    .and_then(|l| l.strip_suffix(':'))
+                {
+                    release = Some(
+                        number
+                            .parse()
+                            .map_err(|_| BaselineError::InvalidLine(i + 1))?,
+                    );
+                } else if !is_section(line) {
+                    consumer = Some(line);
+                }
+            }
+        }
+        Ok(baseline)
+    }
+
+    fn add(
+        &mut self,
+        release: Option<u16>,
+        consumer: &str,
+        dependency: &str,
+        member: Option<&str>,
+        category: Option<FindingKind>,
+    ) {
+        self.reports.entry(release).or_default().insert(Finding {
+            consumer: consumer.to_owned(),
+            dependency: dependency.to_owned(),
+            member: member.map(str::to_owned),
+            category,
+        });
+    }
+
+    /// The findings of the given release. A baseline of a single check is used for any release.
+    fn findings(&self, release: Option<u16>) -> Option<&BTreeSet<Finding>> {
+        match self.reports.get(&release) {
+            Some(findings) => Some(findings),
+            None if self.reports.len() == 1 => self.reports.values().next(),
+            None => None,
+        }
+    }
+
+    /// Removes the findings of the baseline from `requirements`, and the classes left without
+    /// any.
+    pub fn apply(
+        &self,
+        release: Option<u16>,
+        requirements: &mut Vec<ClassRequirements<'_>>,
+    ) -> Diff<'_> {
+        let Some(findings) = self.findings(release) else {
+            if !self.reports.is_empty() {
+                warn!("The baseline has no report for release {release:?}");
+            }
+            return Diff {
+                known: 0,
+                resolved: Vec::new(),
+            };
+        };
+        let mut seen: BTreeSet<&Finding> = BTreeSet::new();
+        let mut known = 0;
+        for r in requirements.iter_mut() {
+            let consumer = r.name();
+            known += r.suppress(|dependency, member, kind| {
+                let mut finding = Finding {
+                    consumer: consumer.to_owned(),
+                    dependency: dependency.to_owned(),
+                    member: member.map(str::to_owned),
+                    category: Some(kind),
+                };
+                //a finding of another kind is new, unless the report does not tell the kind
+                let known = findings.get(&finding).or_else(|| {
+                    finding.category = None;
+                    findings.get(&finding)
+                });
+                if let Some(finding) = known {
+                    seen.insert(finding);
+                }
+                known.is_some()
+            });
+        }
+        requirements.retain(|r| !r.is_empty());
+        let resolved: Vec<&Finding> = findings.iter().filter(|f| !seen.contains(f)).collect();
+        info!(
+            "{known} findings known from the baseline, {} resolved",
+            resolved.len()
+        );
+        Diff { known, resolved }
+    }
+}
+
+/// The headers of the text sections after the requirements.
+fn is_section(line: &str) -> bool {
+    line.starts_with("From ")
+        || line.starts_with("Suppressed findings: ")
+        || line.starts_with("Known findings: ")
+        || [
+            "Duplicate classes",
+            "Missing Class-Path entries",
+            "Failed to read",
+            "Unused suppressions",
+            "Expired suppressions",
+            "Resolved findings",
+        ]
+        .contains(&line)
+}
@@ -0,0 +1,422 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use ahash::{AHashMap, AHashSet};
+pub use java_class::java_class::Access;
+use java_class::{
+    error::InvalidClass,
+    java_class::{Class, FieldInfo, MethodInfo},
+};
+use log::{debug, info};
+use serde::Serialize;
+
+type HashMap<K, V> = AHashMap<K, V>;
+type HashSet<E> = AHashSet<E>;
+
+/// A change of a public class. Methods are named with their descriptor, fields as
+/// `name:descriptor`, like in the reference check.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum Change {
+    ClassRemoved,
+    ClassLessAccessible,
+    //the class became an interface or vice versa
+    KindChanged {
+        interface: bool,
+    },
+    ClassNowFinal,
+    ClassNowAbstract,
+    MethodRemoved {
+        method: String,
+    },
+    //the only method of the same name in the new version
+    MethodChanged {
+        method: String,
+        now: String,
+    },
+    MethodLessAccessible {
+        method: String,
+        from: Access,
+        to: Access,
+    },
+    MethodNowStatic {
+        method: String,
+    },
+    MethodNoLongerStatic {
+        method: String,
+    },
+    MethodNowFinal {
+        method: String,
+    },
+    MethodNowAbstract {
+        method: String,
+    },
+    AbstractMethodAdded {
+        method: String,
+    },
+    FieldRemoved {
+        field: String,
+    },
+    FieldTypeChanged {
+        field: String,
+        now: String,
+    },
+    FieldLessAccessible {
+        field: String,
+        from: Access,
+        to: Access,
+    },
+    FieldNowStatic {
+        field: String,
+    },
+    FieldNoLongerStatic {
+        field: String,
+    },
+    FieldNowFinal {
+        field: String,
+    },
+}
+
+impl Change {
+    /// Whether classes compiled against the old version may fail to link or run against the new
+    /// one. An added abstract method only breaks the compilation of implementations, calling it
+    /// on an old implementation fails with an `AbstractMethodError`.
+    pub fn is_binary_incompatible(&self) -> bool {
+        !matches!(self, Change::AbstractMethodAdded { .. })
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::ClassRemoved => write!(f, "Removed"),
+            Change::ClassLessAccessible => write!(f, "No longer public"),
+            Change::KindChanged { interface: true } => write!(f, "Class is now an interface"),
+            Change::KindChanged { interface: false } => write!(f, "Interface is now a class"),
+            Change::ClassNowFinal => write!(f, "Now final"),
+            Change::ClassNowAbstract => write!(f, "Now abstract"),
+            Change::MethodRemoved { method } => write!(f, "Method {method} removed"),
+            Change::MethodChanged { method, now } => {
+                write!(f, "Method {method} changed to {now}")
+            }
+            Change::MethodLessAccessible { method, from, to } => {
+                write!(f, "Method {method} changed from {from} to {to}")
+            }
+            Change::MethodNowStatic { method } => write!(f, "Method {method} is now static"),
+            Change::MethodNoLongerStatic { method } => {
+                write!(f, "Method {method} is no longer static")
+            }
+            Change::MethodNowFinal { method } => write!(f, "Method {method} is now final"),
+            Change::MethodNowAbstract { method } => write!(f, "Method {method} is now abstract"),
+            Change::AbstractMethodAdded { method } => {
+                write!(f, "Abstract method {method} added")
+            }
+            Change::FieldRemoved { field } => write!(f, "Field {field} removed"),
+            Change::FieldTypeChanged { field, now } => {
+                write!(f, "Field {field} changed to {now}")
+            }
+            Change::FieldLessAccessible { field, from, to } => {
+                write!(f, "Field {field} changed from {from} to {to}")
+            }
+            Change::FieldNowStatic { field } => write!(f, "Field {field} is now static"),
+            Change::FieldNoLongerStatic { field } => {
+                write!(f, "Field {field} is no longer static")
+            }
+            Change::FieldNowFinal { field } => write!(f, "Field {field} is now final"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ApiDifference {
+    pub class: String,
+    #[serde(flatten)]
+    pub change: Change,
+    pub binary_incompatible: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Member {
+    access: Access,
+    is_static: bool,
+    is_final: bool,
+    is_abstract: bool,
+}
+
+impl Member {
+    fn of_method(method: &MethodInfo) -> Self {
+        Member {
+            access: method.access(),
+            is_static: method.is_static(),
+            is_final: method.is_final(),
+            is_abstract: method.is_abstract(),
+        }
+    }
+
+    fn of_field(field: &FieldInfo) -> Self {
+        Member {
+            access: field.access(),
+            is_static: field.is_static(),
+            is_final: field.is_final(),
+            is_abstract: false,
+        }
+    }
+
+    fn is_api(&self) -> bool {
+        self.access >= Access::Protected
+    }
+}
+
+/// The methods and fields of a class, without the ones generated by the compiler.
+struct Members<'a> {
+    methods: BTreeMap<String, Member>,
+    //by name, with the descriptor
+    fields: BTreeMap<&'a str, (&'a str, Member)>,
+}
+
+impl<'a> Members<'a> {
+    fn of(class: &'a Class) -> Result<Self, String> {
+        let mut methods = BTreeMap::new();
+        for method in &class.methods {
+            let name = class.get_utf8(&method.name_index)?;
+            if method.is_synthetic() || method.is_bridge() || name == "<clinit>" {
+                continue;
+            }
+            let descriptor = class.get_utf8(&method.descriptor_index)?;
+            methods.insert(format!("{name}{descriptor}"), Member::of_method(method));
+        }
+        let mut fields = BTreeMap::new();
+        for field in &class.fields {
+            if field.is_synthetic() {
+                continue;
+            }
+            let name = class.get_utf8(&field.name_index)?;
+            let descriptor = class.get_utf8(&field.descriptor_index)?;
+            fields.insert(name, (descriptor, Member::of_field(field)));
+        }
+        Ok(Members { methods, fields })
+    }
+}
+
+/// The supertypes of a class found in `classes`, nearest first.
+fn supertypes<'a>(
+    classes: &'a HashMap<String, Class>,
+    class: &'a Class,
+) -> Result<Vec<&'a Class>, String> {
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![class];
+    while let Some(current) = pending.pop() {
+        let mut names: Vec<&str> = current.get_super_name()?.into_iter().collect();
+        names.extend(current.get_interface_names()?);
+        for name in names {
+            if let Some(supertype) = classes.get(name)
+                && seen.insert(name)
+            {
+                result.push(supertype);
+                pending.push(supertype);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Whether a member moved to a supertype, where it is still found by the old references.
+fn is_inherited(
+    supertypes: &[&Class],
+    found: impl Fn(&Members) -> Option<Member>,
+    old: &Member,
+) -> Result<bool, String> {
+    for supertype in supertypes {
+        if let Some(member) = found(&Members::of(supertype)?)
+            && member.access >= old.access
+            && member.is_static == old.is_static
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn compare_class(
+    old: &Class,
+    new: &Class,
+    new_classes: &HashMap<String, Class>,
+) -> Result<Vec<Change>, String> {
+    //every member is incompatible then, no need to list them
+    if !new.is_public() {
+        return Ok(vec![Change::ClassLessAccessible]);
+    }
+    if old.is_interface() != new.is_interface() {
+        return Ok(vec![Change::KindChanged {
+            interface: new.is_interface(),
+        }]);
+    }
+    let mut changes = Vec::new();
+    if !old.is_interface() {
+        if new.is_final() && !old.is_final() {
+            changes.push(Change::ClassNowFinal);
+        }
+        if new.is_abstract() && !old.is_abstract() {
+            changes.push(Change::ClassNowAbstract);
+        }
+    }
+
+    let old_members = Members::of(old)?;
+    let new_members = Members::of(new)?;
+    let supertypes = supertypes(new_classes, new)?;
+    //new methods that replace a removed one
+    let mut replacements = HashSet::new();
+    for (method, member) in old_members.methods.iter().filter(|(_, m)| m.is_api()) {
+        let Some(now) = new_members.methods.get(method) else {
+            let found = |members: &Members| members.methods.get(method).copied();
+            if is_inherited(&supertypes, found, member)? {
+                continue;
+            }
+            let name = &method[..method.find('(').unwrap_or(method.len())];
+            let mut candidates = new_members.methods.iter().filter(|(m, now)| {
+                now.is_api()
+                    && !old_members.methods.contains_key(*m)
+                    && m.starts_with(name)
+                    && m[name.len()..].starts_with('(')
+            });
+            match (candidates.next(), candidates.next()) {
+                (Some((now, _)), None) => {
+                    replacements.insert(now.as_str());
+                    changes.push(Change::MethodChanged {
+                        method: method.clone(),
+                        now: now.clone(),
+                    })
+                }
+                _ => changes.push(Change::MethodRemoved {
+                    method: method.clone(),
+                }),
+            }
+            continue;
+        };
+        let method = || method.clone();
+        if now.access < member.access {
+            changes.push(Change::MethodLessAccessible {
+                method: method(),
+                from: member.access,
+                to: now.access,
+            });
+        }
+        match (member.is_static, now.is_static) {
+            (false, true) => changes.push(Change::MethodNowStatic { method: method() }),
+            (true, false) => changes.push(Change::MethodNoLongerStatic { method: method() }),
+            _ => {}
+        }
+        if now.is_final && !member.is_final && !old.is_final() {
+            changes.push(Change::MethodNowFinal { method: method() });
+        }
+        if now.is_abstract && !member.is_abstract {
+            changes.push(Change::MethodNowAbstract { method: method() });
+        }
+    }
+    for (method, member) in &new_members.methods {
+        if member.is_api()
+            && member.is_abstract
+            && !old_members.methods.contains_key(method)
+            && !replacements.contains(method.as_str())
+        {
+            changes.push(Change::AbstractMethodAdded {
+                method: method.clone(),
+            });
+        }
+    }
+
+    for (name, (descriptor, member)) in old_members.fields.iter().filter(|(_, (_, m))| m.is_api()) {
+        let field = || format!("{name}:{descriptor}");
+        let Some((now_descriptor, now)) = new_members.fields.get(name) else {
+            let found = |members: &Members| {
+                members
+                    .fields
+                    .get(name)
+                    .filter(|(d, _)| d == descriptor)
+                    .map(|(_, m)| *m)
+            };
+            if !is_inherited(&supertypes, found, member)? {
+                changes.push(Change::FieldRemoved { field: field() });
+            }
+            continue;
+        };
+        if now_descriptor != descriptor {
+            changes.push(Change::FieldTypeChanged {
+                field: field(),
+                now: format!("{name}:{now_descriptor}"),
+            });
+            continue;
+        }
+        if now.access < member.access {
+            changes.push(Change::FieldLessAccessible {
+                field: field(),
+                from: member.access,
+                to: now.access,
+            });
+        }
+        match (member.is_static, now.is_static) {
+            (false, true) => changes.push(Change::FieldNowStatic { field: field() }),
+            (true, false) => changes.push(Change::FieldNoLongerStatic { field: field() }),
+            _ => {}
+        }
+        if now.is_final && !member.is_final {
+            changes.push(Change::FieldNowFinal { field: field() });
+        }
+    }
+    Ok(changes)
+}
+
+/// Compares the public API of two versions of the same classes.
+pub fn compare(
+    old: &HashMap<String, Class>,
+    new: &HashMap<String, Class>,
+) -> Result<Vec<ApiDifference>, InvalidClass> {
+    info!("Comparing {} old with {} new classes", old.len(), new.len());
+    let mut differences = Vec::new();
+    for (name, class) in old {
+        if !class.is_public() || class.is_module() {
+            continue;
+        }
+        let changes = match new.get(name) {
+            Some(new_class) => compare_class(class, new_class, new)
+                .map_err(|cause| InvalidClass::new(name, cause))?,
+            None => vec![Change::ClassRemoved],
+        };
+        debug!("{} changes to {name}", changes.len());
+        differences.extend(changes.into_iter().map(|change| ApiDifference {
+            class: name.clone(),
+            binary_incompatible: change.is_binary_incompatible(),
+            change,
+        }));
+    }
+    differences.sort();
+    info!("{} differences found", differences.len());
+    Ok(differences)
+}
+
+/// Formats the differences grouped by class.
+pub fn format(differences: &[ApiDifference]) -> String {
+    let mut result = String::new();
+    let mut class = None;
+    for difference in differences {
+        if class != Some(&difference.class) {
+            class = Some(&difference.class);
+            result.push_str(format!("{}\n", difference.class).as_str());
+        }
+        let compatibility = if difference.binary_incompatible {
+            "binary incompatible"
+        } else {
+            "binary compatible"
+        };
+        result.push_str(format!("\t{} ({compatibility})\n", difference.change).as_str());
+    }
+    result
+}
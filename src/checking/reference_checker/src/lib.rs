@@ -6,15 +6,20 @@
 * SPDX-License-Identifier: MPL-2.0
 */
 
-use std::{collections::hash_map::Entry, hash::Hash};
+use std::{
+    collections::{BTreeMap, BTreeSet, hash_map::Entry},
+    hash::Hash,
+};
 
 use ahash::{AHashMap, AHashSet};
 use java_class::{
     classinfo::{ClassInfo, Method},
-    java_class::{Class, ConstPoolEntry},
+    error::InvalidClass,
+    java_class::{Access, Class, ClassUsage, ConstPoolEntry, MemberUsage},
 };
 use log::{debug, info, trace};
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize, Serializer, ser::SerializeStruct};
 
 type HashMap<K, V> = AHashMap<K, V>;
 type HashSet<E> = AHashSet<E>;
@@ -22,6 +27,10 @@ type HashSet<E> = AHashSet<E>;
 #[derive(Debug, Eq)]
 pub struct ClassRequirements<'a> {
     name: &'a str,
+    //the nest of the class, whose members may access each other's private members
+    nest_host: &'a str,
+    //the superclasses found on the class path, nearest first
+    superclasses: Vec<&'a str>,
     dependencies: HashMap<&'a str, Dependency>,
 }
 
@@ -66,108 +75,369 @@ impl PartialEq for ClassRequirements<'_> {
 }
 
 impl<'a> ClassRequirements<'a> {
-    fn remove_class(&mut self, name: &'a str, interface: bool) {
+    fn remove_class(&mut self, name: &'a str, interface: bool, public: bool) {
         trace!("Removing class {} from {}", name, self.name);
+        let accessible = public || package(name) == package(self.name);
         if let Entry::Occupied(mut o) = self.dependencies.entry(name) {
             let dep = o.get_mut();
-            //interface flag does not matter if there are no methods missing
-            if dep.methods.is_empty() || dep.is_interface == interface {
+            if !accessible {
+                //resolving the class fails already, whatever the members
                 dep.class_dep = false;
+                dep.inaccessible = true;
+                dep.methods.clear();
+                dep.fields.clear();
+                dep.static_mismatches.clear();
+                dep.inaccessible_members.clear();
+                return;
+            }
+            //interface flag only matters for missing methods and the class hierarchy (fields never
+            //care)
+            let kind_matters = !dep.methods.is_empty()
+                || dep.usages.contains(&ClassUsage::Superclass)
+                || dep.usages.contains(&ClassUsage::Interface);
+            if !kind_matters || dep.is_interface == interface {
+                dep.class_dep = false;
+            } else {
+                dep.kind_mismatch = true;
             }
         }
     }
 
     fn clear_empty_deps(&mut self) {
-        self.dependencies
-            .retain(|_, dep| dep.class_dep || !dep.methods.is_empty());
+        self.dependencies.retain(|_, dep| {
+            dep.class_dep
+                || dep.inaccessible
+                || !dep.methods.is_empty()
+                || !dep.fields.is_empty()
+                || !dep.static_mismatches.is_empty()
+                || !dep.inaccessible_members.is_empty()
+                || !dep.unimplemented.is_empty()
+        });
     }
 
-    fn remove_methods<'b>(&mut self, class: &'a str, provider: &'b MethodProvider<'b>)
+    fn remove_members<'b>(&mut self, class: &'a str, provider: &'b MemberProvider<'b>)
     where
         'a: 'b,
     {
         trace!(
-            "Removing methods {:?} of class {} from {}",
-            &provider.methods, class, self.name
+            "Removing methods {:?} and fields {:?} of class {} from {}",
+            &provider.methods, &provider.fields, class, self.name
         );
+        let inaccessible: HashMap<String, Access> = match self.dependencies.get(class) {
+            Some(dep) => dep
+                .methods
+                .iter()
+                .chain(&dep.fields)
+                .filter_map(|sig| {
+                    let declaration = provider.declarations.get(sig)?;
+                    (!self.can_access(provider, declaration))
+                        .then(|| (sig.clone(), declaration.access))
+                })
+                .collect(),
+            None => HashMap::new(),
+        };
         let entry = self.dependencies.entry(class);
 
         if let Entry::Occupied(mut e) = entry {
             let entry = &mut e.get_mut();
+            match &provider.fields {
+                Some(fields) => entry.fields.retain(|field| !fields.contains(field)),
+                //some JDK superclass lacks field information, so assume they are all present
+                None => entry.fields.clear(),
+            }
+            for (field, access) in inaccessible.iter().filter(|(sig, _)| !sig.contains('(')) {
+                entry.inaccessible_members.insert(field.clone(), *access);
+            }
             if entry.is_interface != provider.interface {
                 return;
             }
-            let value = &mut entry.methods;
-            for sig in provider.methods.keys() {
+            for (sig, method) in &provider.methods {
                 trace!("Trying to remove {}#{}", class, sig.as_str());
-                if value.remove(sig) {
+                if entry.methods.remove(sig) {
                     trace!("Removed {}#{}", class, sig.as_str());
-                    trace!("Remaining methods for {}: {:?}", class, value);
+                    trace!("Remaining methods for {}: {:?}", class, entry.methods);
+                    match inaccessible.get(sig) {
+                        Some(access) => {
+                            entry.inaccessible_members.insert(sig.clone(), *access);
+                        }
+                        None => entry.check_static(sig, method),
+                    }
                 }
             }
         }
         self.clear_empty_deps();
     }
 
+    /// Whether the class may access a member of `target`, see JVMS 5.4.4. A protected instance
+    /// member also requires `target` to be related to the class, as the object it is accessed on
+    /// is of that type.
+    fn can_access(&self, target: &MemberProvider, declaration: &Declaration) -> bool {
+        let same_package = || package(declaration.class) == package(self.name);
+        match declaration.access {
+            Access::Public => true,
+            Access::Private => declaration.nest_host == self.nest_host,
+            Access::Package => same_package(),
+            Access::Protected => {
+                same_package()
+                    || (self.is_subclass_of(declaration.class)
+                        && (declaration.is_static
+                            || self.is_subclass_of(target.name)
+                            || target.superclasses.contains(&self.name)))
+            }
+        }
+    }
+
+    /// Adds the abstract methods the class inherits without implementing them.
+    fn add_unimplemented(
+        &mut self,
+        classes: &'a HashMap<String, Class>,
+        java_classes: &HashMap<&'a str, ClassInfo>,
+    ) -> Result<(), String> {
+        for (class, interface, method) in find_unimplemented(self.name, classes, java_classes)? {
+            self.dependencies
+                .entry(class)
+                .or_insert_with(|| Dependency::new(interface, false))
+                .unimplemented
+                .insert(method);
+        }
+        Ok(())
+    }
+
+    fn is_subclass_of(&self, class: &str) -> bool {
+        self.name == class || self.superclasses.contains(&class)
+    }
+
     fn remove_java_classes_and_methods(&mut self, java_classes: &HashMap<&'a str, ClassInfo>) {
+        //the classinfo files only list the public and protected classes and members
         java_classes
             .iter()
-            .for_each(|(cls, cls_info)| self.remove_class(cls, cls_info.is_interface));
+            .for_each(|(cls, cls_info)| self.remove_class(cls, cls_info.is_interface, true));
         for (class_name, dep) in self.dependencies.iter_mut() {
-            dep.methods
-                .retain(|method| !Self::provides_method(class_name, method, java_classes));
+            let found: Vec<(String, &Method)> = dep
+                .methods
+                .iter()
+                .filter_map(|sig| {
+                    Self::find_method(class_name, sig, java_classes).map(|m| (sig.clone(), m))
+                })
+                .collect();
+            for (sig, method) in found {
+                dep.methods.remove(&sig);
+                dep.check_static(&sig, method);
+            }
+            dep.fields
+                .retain(|field| !Self::provides_field(class_name, field, java_classes));
         }
         self.clear_empty_deps();
     }
 
-    fn provides_method(class: &str, method: &str, java_classes: &HashMap<&str, ClassInfo>) -> bool {
-        if let Some(class_info) = java_classes.get("java/lang/Object")
-            && !method.contains("<init>")
-            && class_info.methods.contains_key(method)
-        {
-            return true;
-        }
+    /// The method as declared by the class or its supertypes, like the JVM resolves it.
+    fn find_method<'c>(
+        class: &str,
+        method: &str,
+        java_classes: &'c HashMap<&str, ClassInfo>,
+    ) -> Option<&'c Method> {
         if let Some(class_info) = java_classes.get(class) {
-            if class_info.methods.contains_key(method) {
-                return true;
+            if let Some(found) = class_info.methods.get(method) {
+                return Some(found);
             }
             if class_info.methods.values().any(|m| m.polymorphic_signature) {
                 let Some((method_name, _)) = method.split_once("(") else {
                     panic!("Illegal method signature! {}", method)
                 };
-                for class_method in class_info.methods.values().filter_map(|m| {
-                    if m.polymorphic_signature {
-                        let Some((name, _)) = method.split_once("(") else {
-                            panic!("Illegal method signature! {}", method)
-                        };
-                        return Some(name);
-                    }
-                    None
-                }) {
-                    if method_name == class_method {
-                        return true;
+                for class_method in class_info
+                    .methods
+                    .values()
+                    .filter(|m| m.polymorphic_signature)
+                {
+                    let Some((name, _)) = class_method.signature.split_once("(") else {
+                        panic!("Illegal method signature! {}", class_method.signature)
+                    };
+                    if method_name == name {
+                        return Some(class_method);
                     }
                 }
             }
             if let Some(super_class) = class_info.super_class
-                && Self::provides_method(super_class, method, java_classes)
+                && let Some(found) = Self::find_method(super_class, method, java_classes)
             {
-                return true;
+                return Some(found);
             }
             for super_iface in &class_info.interfaces {
-                if Self::provides_method(super_iface, method, java_classes) {
-                    return true;
+                if let Some(found) = Self::find_method(super_iface, method, java_classes) {
+                    return Some(found);
                 }
             }
         }
-        false
+        //interfaces have the public methods of Object, too
+        if let Some(class_info) = java_classes.get("java/lang/Object")
+            && !method.contains("<init>")
+        {
+            return class_info.methods.get(method);
+        }
+        None
     }
 
-    fn is_empty(&self) -> bool {
+    fn provides_field(class: &str, field: &str, java_classes: &HashMap<&str, ClassInfo>) -> bool {
+        let Some(class_info) = java_classes.get(class) else {
+            return false;
+        };
+        match &class_info.fields {
+            Some(fields) if fields.contains(field) => return true,
+            Some(_) => (),
+            None => return true,
+        }
+        //fields are looked up in the superinterfaces before the superclass
+        class_info
+            .interfaces
+            .iter()
+            .any(|iface| Self::provides_field(iface, field, java_classes))
+            || class_info
+                .super_class
+                .is_some_and(|super_class| Self::provides_field(super_class, field, java_classes))
+    }
+
+    pub fn is_empty(&self) -> bool {
         self.dependencies.is_empty()
     }
 
+    /// Splits the requirements by a key of the required classes, like the archive providing them.
+    pub fn partition_by<K, F>(&self, key: F) -> BTreeMap<K, ClassRequirements<'a>>
+    where
+        K: Ord,
+        F: Fn(&str) -> K,
+    {
+        let mut result: BTreeMap<K, ClassRequirements<'a>> = BTreeMap::new();
+        for (name, dep) in &self.dependencies {
+            result
+                .entry(key(name))
+                .or_insert_with(|| ClassRequirements {
+                    name: self.name,
+                    nest_host: self.nest_host,
+                    superclasses: self.superclasses.clone(),
+                    dependencies: HashMap::default(),
+                })
+                .dependencies
+                .insert(name, dep.clone());
+        }
+        result
+    }
+
+    /// Removes the findings for which `suppressed` returns true and returns their number.
+    /// A missing class or a class/interface mismatch is a single finding, passed without member.
+    /// Otherwise every missing method and field of the class is passed separately.
+    pub fn suppress<F>(&mut self, mut suppressed: F) -> usize
+    where
+        F: FnMut(&str, Option<&str>, FindingKind) -> bool,
+    {
+        let mut count = 0;
+        self.dependencies.retain(|name, dep| {
+            let kind = if dep.kind_mismatch {
+                Some(FindingKind::KindMismatch)
+            } else if dep.inaccessible {
+                Some(FindingKind::IllegalAccess)
+            } else if dep.class_dep {
+                Some(FindingKind::MissingClass)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                let remove = suppressed(name, None, kind);
+                count += usize::from(remove);
+                return !remove;
+            }
+            let before = dep.finding_count();
+            dep.methods
+                .retain(|method| !suppressed(name, Some(method), FindingKind::MissingMethod));
+            dep.fields
+                .retain(|field| !suppressed(name, Some(field), FindingKind::MissingField));
+            dep.static_mismatches
+                .retain(|method, _| !suppressed(name, Some(method), FindingKind::StaticMismatch));
+            dep.inaccessible_members
+                .retain(|member, _| !suppressed(name, Some(member), FindingKind::IllegalAccess));
+            dep.unimplemented
+                .retain(|method| !suppressed(name, Some(method), FindingKind::UnimplementedMethod));
+            count += before - dep.finding_count();
+            dep.finding_count() > 0
+        });
+        count
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The unmet dependencies, sorted by class name.
+    pub fn dependencies(&self) -> Vec<UnmetDependency<'_>> {
+        let mut result: Vec<UnmetDependency> = self
+            .dependencies
+            .iter()
+            .map(|(name, dep)| {
+                let mut methods: Vec<&str> = dep.methods.iter().map(String::as_str).collect();
+                methods.sort();
+                let mut fields: Vec<&str> = dep.fields.iter().map(String::as_str).collect();
+                fields.sort();
+                let mut static_mismatches: Vec<StaticMismatch> = dep
+                    .static_mismatches
+                    .iter()
+                    .map(|(method, &is_static)| StaticMismatch {
+                        method,
+                        is_static,
+                        usages: dep.invocations[method]
+                            .iter()
+                            .copied()
+                            .filter(|usage| usage.is_static() != is_static)
+                            .collect(),
+                    })
+                    .collect();
+                static_mismatches.sort_by_key(|m| m.method);
+                let mut inaccessible_members: Vec<InaccessibleMember> = dep
+                    .inaccessible_members
+                    .iter()
+                    .map(|(member, &access)| InaccessibleMember { member, access })
+                    .collect();
+                inaccessible_members.sort_by_key(|m| (!m.member.contains('('), m.member));
+                let mut unimplemented_methods: Vec<&str> =
+                    dep.unimplemented.iter().map(String::as_str).collect();
+                unimplemented_methods.sort();
+                UnmetDependency {
+                    name,
+                    kind: if dep.is_interface {
+                        DependencyKind::Interface
+                    } else {
+                        DependencyKind::Class
+                    },
+                    status: if dep.kind_mismatch {
+                        DependencyStatus::KindMismatch
+                    } else if dep.inaccessible {
+                        DependencyStatus::Inaccessible
+                    } else if dep.class_dep {
+                        DependencyStatus::Missing
+                    } else {
+                        DependencyStatus::ApiMismatch
+                    },
+                    methods,
+                    fields,
+                    static_mismatches,
+                    inaccessible_members,
+                    unimplemented_methods,
+                    usages: dep.usages.iter().copied().collect(),
+                }
+            })
+            .collect();
+        result.sort_by_key(|d| d.name);
+        result
+    }
+
     pub fn format(&'a self) -> String {
+        self.format_with_providers(|_| None)
+    }
+
+    /// Formats the requirements, naming the archive returned by `provider` for every required
+    /// class found on the class path. Such a class only partially matches the requirements.
+    pub fn format_with_providers<F>(&'a self, provider: F) -> String
+    where
+        F: Fn(&str) -> Option<String>,
+    {
         let mut result = self.name.to_owned();
         result.push('\n');
         let mut sorted: Vec<(&'a str, &Dependency)> = self
@@ -198,7 +468,14 @@ impl<'a> ClassRequirements<'a> {
             );
             result.push_str(cls.as_str());
             if !entry.1.class_dep {
-                result.push_str(" (API mismatch)");
+                if entry.1.inaccessible {
+                    result.push_str(" (inaccessible)");
+                } else {
+                    result.push_str(" (API mismatch)");
+                }
+            }
+            if let Some(archive) = provider(entry.0) {
+                result.push_str(format!(" (provided by {archive})").as_str());
             }
             result.push('\n');
             let mut sorted: Vec<&'a str> = entry.1.methods.iter().map(|s| s.as_str()).collect();
@@ -208,22 +485,202 @@ impl<'a> ClassRequirements<'a> {
                 result.push_str(format!("Method {}", method).as_str());
                 result.push('\n');
             }
+            let mut sorted: Vec<&'a str> = entry.1.fields.iter().map(|s| s.as_str()).collect();
+            sorted.sort();
+            for field in sorted {
+                result.push_str("\t\t");
+                result.push_str(format!("Field {}", field).as_str());
+                result.push('\n');
+            }
+            let mut sorted: Vec<(&String, &bool)> = entry.1.static_mismatches.iter().collect();
+            sorted.sort();
+            for (method, &is_static) in sorted {
+                let usages: Vec<String> = entry.1.invocations[method]
+                    .iter()
+                    .filter(|usage| usage.is_static() != is_static)
+                    .map(|usage| usage.to_string())
+                    .collect();
+                result.push_str(
+                    format!(
+                        "\t\tMethod {method} is {}static (usage: {})\n",
+                        if is_static { "" } else { "not " },
+                        usages.join(", ")
+                    )
+                    .as_str(),
+                );
+            }
+            let mut sorted: Vec<(&String, &Access)> = entry.1.inaccessible_members.iter().collect();
+            //methods first, like the missing members
+            sorted.sort_by_key(|(member, _)| (!member.contains('('), *member));
+            for (member, access) in sorted {
+                let kind = if member.contains('(') {
+                    "Method"
+                } else {
+                    "Field"
+                };
+                result.push_str(format!("\t\t{kind} {member} is {access}\n").as_str());
+            }
+            let mut sorted: Vec<&String> = entry.1.unimplemented.iter().collect();
+            sorted.sort();
+            for method in sorted {
+                result.push_str(format!("\t\tMethod {method} is not implemented\n").as_str());
+            }
         }
         result
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Serialized as `{"name", "dependencies"}`, see [`UnmetDependency`] for the dependencies.
+impl Serialize for ClassRequirements<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ClassRequirements", 2)?;
+        state.serialize_field("name", self.name)?;
+        state.serialize_field("dependencies", &self.dependencies())?;
+        state.end()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyKind {
+    Class,
+    Interface,
+}
+
+/// The kind of a single finding, see `ClassRequirements::suppress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    MissingClass,
+    MissingMethod,
+    MissingField,
+    KindMismatch,
+    StaticMismatch,
+    //an inaccessible class or member
+    IllegalAccess,
+    UnimplementedMethod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyStatus {
+    //the class is not found at all
+    Missing,
+    //the class is found, but lacks some of the required members
+    ApiMismatch,
+    //the class is found, but is an interface where a class is required or vice versa
+    KindMismatch,
+    //the class is found, but is neither public nor in the package of the consumer
+    Inaccessible,
+}
+
+/// A required class with the members it lacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnmetDependency<'a> {
+    pub name: &'a str,
+    pub kind: DependencyKind,
+    pub status: DependencyStatus,
+    pub methods: Vec<&'a str>,
+    pub fields: Vec<&'a str>,
+    pub static_mismatches: Vec<StaticMismatch<'a>>,
+    pub inaccessible_members: Vec<InaccessibleMember<'a>>,
+    //abstract methods of the class the consumer lacks an implementation of
+    pub unimplemented_methods: Vec<&'a str>,
+    //how the consumer uses the class, in the order of `ClassUsage`
+    pub usages: Vec<ClassUsage>,
+}
+
+/// A required method that exists, but is static where an instance method is required or vice
+/// versa.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaticMismatch<'a> {
+    pub method: &'a str,
+    //whether the existing method is static
+    pub is_static: bool,
+    //the usages requiring the other kind of method
+    pub usages: Vec<MemberUsage>,
+}
+
+/// A required method or field that exists, but that the consumer may not access.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InaccessibleMember<'a> {
+    pub member: &'a str,
+    pub access: Access,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Dependency {
     methods: HashSet<String>,
+    fields: HashSet<String>,
     is_interface: bool,
     class_dep: bool,
+    //the required class exists, but is of the other kind
+    kind_mismatch: bool,
+    usages: BTreeSet<ClassUsage>,
+    //how every required method is used
+    invocations: HashMap<String, BTreeSet<MemberUsage>>,
+    //methods found with the other static flag, and whether they are static
+    static_mismatches: HashMap<String, bool>,
+    //the required class exists, but is not public and in another package
+    inaccessible: bool,
+    //members found, but not accessible to the consumer, with their access
+    inaccessible_members: HashMap<String, Access>,
+    //abstract methods of the class that the consumer inherits without implementing them
+    unimplemented: HashSet<String>,
 }
 
 impl Dependency {
-    fn add(&mut self, method: String) {
+    fn new(is_interface: bool, class_dep: bool) -> Self {
+        Dependency {
+            methods: HashSet::new(),
+            fields: HashSet::new(),
+            is_interface,
+            class_dep,
+            kind_mismatch: false,
+            usages: BTreeSet::new(),
+            invocations: HashMap::new(),
+            static_mismatches: HashMap::new(),
+            inaccessible: false,
+            inaccessible_members: HashMap::new(),
+            unimplemented: HashSet::new(),
+        }
+    }
+
+    /// The number of member findings.
+    fn finding_count(&self) -> usize {
+        self.methods.len()
+            + self.fields.len()
+            + self.static_mismatches.len()
+            + self.inaccessible_members.len()
+            + self.unimplemented.len()
+    }
+
+    fn add(&mut self, method: String, usages: &BTreeSet<MemberUsage>) {
+        self.invocations
+            .entry(method.clone())
+            .or_default()
+            .extend(usages);
         self.methods.insert(method);
     }
+
+    /// Records a found method if it is invoked as a static method but is an instance method or
+    /// vice versa, which the JVM rejects with an `IncompatibleClassChangeError`.
+    fn check_static(&mut self, sig: &str, method: &Method) {
+        let Some(is_static) = method.is_static else {
+            return;
+        };
+        if self
+            .invocations
+            .get(sig)
+            .is_some_and(|usages| usages.iter().any(|usage| usage.is_static() != is_static))
+        {
+            self.static_mismatches.insert(sig.to_owned(), is_static);
+        }
+    }
+
+    fn add_field(&mut self, field: String) {
+        self.fields.insert(field);
+    }
 }
 
 trait Consumer<'a> {
@@ -231,11 +688,11 @@ trait Consumer<'a> {
 }
 
 trait Provider {
-    fn get_provided(
-        &self,
-        classes: &HashMap<String, Class>,
+    fn get_provided<'a>(
+        &'a self,
+        classes: &'a HashMap<String, Class>,
         java_classes: &HashMap<&str, ClassInfo>,
-    ) -> Result<Option<MethodProvider<'_>>, String>;
+    ) -> Result<Option<MemberProvider<'a>>, String>;
 }
 
 impl<'a> Consumer<'a> for Class {
@@ -245,12 +702,24 @@ impl<'a> Consumer<'a> for Class {
         let mut class_imports = vec![];
         let mut required_class_methods = vec![];
         let mut required_iface_methods = vec![];
+        let mut required_fields = vec![];
         let this_name = self.get_name()?;
+        let usages = self.get_usages()?;
         for cp_info in &self.const_pool {
+            //references that no instruction or method handle uses are never resolved
+            if matches!(
+                cp_info.1,
+                ConstPoolEntry::MethodRef { .. }
+                    | ConstPoolEntry::IfaceMethodRef { .. }
+                    | ConstPoolEntry::FieldRef { .. }
+            ) && !usages.members.contains_key(cp_info.0)
+            {
+                continue;
+            }
             if let (idx, ConstPoolEntry::Class { name_index }) = cp_info {
-                if !self.is_class_entry_used(idx) {
+                let Some(usage) = usages.classes.get(idx) else {
                     continue;
-                }
+                };
                 //remove array stuff around class definition
                 let trimmed = self
                     .get_utf8(name_index)?
@@ -258,7 +727,7 @@ impl<'a> Consumer<'a> for Class {
                     .trim_start_matches('L')
                     .trim_end_matches(';');
                 if !PRIMITIVES.contains(&trimmed) {
-                    class_imports.push(trimmed);
+                    class_imports.push((trimmed, usage));
                 }
             }
             if let (
@@ -270,7 +739,9 @@ impl<'a> Consumer<'a> for Class {
             ) = cp_info
             {
                 match process_method(class_index, name_type_index, self)? {
-                    Some(res) => required_class_methods.push(res),
+                    Some((cls, method)) => {
+                        required_class_methods.push((cls, method, &usages.members[cp_info.0]))
+                    }
                     None => continue,
                 }
             }
@@ -283,52 +754,66 @@ impl<'a> Consumer<'a> for Class {
             ) = cp_info
             {
                 match process_method(class_index, name_type_index, self)? {
-                    Some(res) => required_iface_methods.push(res),
+                    Some((cls, method)) => {
+                        required_iface_methods.push((cls, method, &usages.members[cp_info.0]))
+                    }
                     None => continue,
                 }
             }
+            if let (
+                _,
+                ConstPoolEntry::FieldRef {
+                    class_index,
+                    name_type_index,
+                },
+            ) = cp_info
+            {
+                required_fields.push(process_field(class_index, name_type_index, self)?);
+            }
         }
 
         let mut deps = HashMap::new();
-        for (cls, method) in required_class_methods {
-            let entry = deps.entry(cls).or_insert(Dependency {
-                methods: HashSet::new(),
-                is_interface: false,
-                class_dep: false,
-            });
+        for (cls, method, method_usages) in required_class_methods {
+            let entry = deps
+                .entry(cls)
+                .or_insert_with(|| Dependency::new(false, false));
             if entry.is_interface {
                 panic!(
                     "Trying to add class method to iface dependency! Method: {} | Class: {}",
                     method, cls
                 )
             }
-            entry.add(method);
+            entry.add(method, method_usages);
         }
-        for (cls, method) in required_iface_methods {
-            let entry = deps.entry(cls).or_insert(Dependency {
-                methods: HashSet::new(),
-                is_interface: true,
-                class_dep: false,
-            });
+        for (cls, method, method_usages) in required_iface_methods {
+            let entry = deps
+                .entry(cls)
+                .or_insert_with(|| Dependency::new(true, false));
             if !entry.is_interface {
                 panic!(
                     "Trying to add iface method to class dependency! Method: {} | Class: {}",
                     method, cls
                 )
             }
-            entry.add(method);
+            entry.add(method, method_usages);
         }
-        for cls in class_imports {
+        for (cls, field) in required_fields {
+            //field references do not tell classes and interfaces apart
             deps.entry(cls)
-                .or_insert(Dependency {
-                    methods: HashSet::new(),
-                    is_interface: false,
-                    class_dep: true,
-                })
-                .class_dep = true;
+                .or_insert_with(|| Dependency::new(false, false))
+                .add_field(field);
+        }
+        for (cls, usage) in class_imports {
+            let entry = deps
+                .entry(cls)
+                .or_insert_with(|| Dependency::new(usage.contains(&ClassUsage::Interface), true));
+            entry.class_dep = true;
+            entry.usages.extend(usage);
         }
         Ok(ClassRequirements {
             name: this_name,
+            nest_host: self.get_nest_host()?,
+            superclasses: Vec::new(),
             dependencies: deps,
         })
     }
@@ -339,14 +824,14 @@ fn process_method<'a>(
     name_type_index: &u16,
     class: &'a Class,
 ) -> Result<Option<(&'a str, String)>, String> {
-    let ConstPoolEntry::Class { name_index } = &class.const_pool[class_index] else {
+    let Some(ConstPoolEntry::Class { name_index }) = class.const_pool.get(class_index) else {
         return Err(format!("Not a class info entry at idx {class_index}!"));
     };
     let class_name = class.get_utf8(name_index)?;
-    let ConstPoolEntry::NameAndType {
+    let Some(ConstPoolEntry::NameAndType {
         name_index: method_name_index,
         descriptor_index,
-    } = &class.const_pool[name_type_index]
+    }) = class.const_pool.get(name_type_index)
     else {
         return Err(format!("Not a NameAndType entry at idx {name_type_index}!"));
     };
@@ -361,37 +846,76 @@ fn process_method<'a>(
     )))
 }
 
+fn process_field<'a>(
+    class_index: &u16,
+    name_type_index: &u16,
+    class: &'a Class,
+) -> Result<(&'a str, String), String> {
+    let Some(ConstPoolEntry::Class { name_index }) = class.const_pool.get(class_index) else {
+        return Err(format!("Not a class info entry at idx {class_index}!"));
+    };
+    let class_name = class.get_utf8(name_index)?;
+    let Some(ConstPoolEntry::NameAndType {
+        name_index: field_name_index,
+        descriptor_index,
+    }) = class.const_pool.get(name_type_index)
+    else {
+        return Err(format!("Not a NameAndType entry at idx {name_type_index}!"));
+    };
+    let field_name = class.get_utf8(field_name_index)?;
+    let field_descriptor = class.get_utf8(descriptor_index)?;
+    Ok((class_name, format!("{field_name}:{field_descriptor}")))
+}
+
 #[derive(Debug)]
-struct MethodProvider<'a> {
+struct MemberProvider<'a> {
     name: &'a str,
     interface: bool,
+    public: bool,
+    //the superclasses found on the class path, nearest first
+    superclasses: Vec<&'a str>,
     methods: HashMap<String, Method>,
+    //None if the fields could not be determined completely
+    fields: Option<HashSet<String>>,
+    //the declarations of the methods and fields found on the class path, JDK members are public
+    //or protected members of classes no consumer is in the package of
+    declarations: HashMap<String, Declaration<'a>>,
+}
+
+/// The class declaring a member and the access to it.
+#[derive(Debug, Clone, Copy)]
+struct Declaration<'a> {
+    class: &'a str,
+    nest_host: &'a str,
+    access: Access,
+    is_static: bool,
 }
 
 impl Provider for Class {
-    fn get_provided(
-        &self,
-        classes: &HashMap<String, Class>,
+    fn get_provided<'a>(
+        &'a self,
+        classes: &'a HashMap<String, Class>,
         java_classes: &HashMap<&str, ClassInfo>,
-    ) -> Result<Option<MethodProvider<'_>>, String> {
+    ) -> Result<Option<MemberProvider<'a>>, String> {
         let mut result = HashMap::default();
-        if let &ConstPoolEntry::Class { name_index } = &self.const_pool[&self.this_class_idx] {
-            let class_name = self.get_utf8(&name_index)?;
-            if !self.is_module() {
-                trace!("Processing class {}", class_name);
-                for (signature, method) in collect_methods(class_name, classes, java_classes)? {
-                    result.insert(signature, method);
-                }
-                return Ok(Some(MethodProvider {
-                    name: class_name,
-                    interface: self.is_interface(),
-                    methods: result,
-                }));
-            }
+        let class_name = self.get_name()?;
+        if self.is_module() {
             trace!("Skipping module-info.class");
             return Ok(None);
         }
-        Err("This-class index is invalid!".to_owned())
+        trace!("Processing class {}", class_name);
+        for (signature, method) in collect_methods(class_name, classes, java_classes)? {
+            result.insert(signature, method);
+        }
+        Ok(Some(MemberProvider {
+            name: class_name,
+            interface: self.is_interface(),
+            public: self.is_public(),
+            superclasses: collect_superclasses(class_name, classes)?,
+            methods: result,
+            fields: collect_fields(class_name, classes, java_classes)?,
+            declarations: collect_declarations(class_name, classes)?,
+        }))
     }
 }
 
@@ -403,61 +927,353 @@ fn collect_methods(
     let mut result = HashMap::default();
     if let Some(current_class) = classes.get(class_name) {
         trace!("Class {}", class_name);
-        for method_signature in current_class.get_methods()? {
+        for method_info in &current_class.methods {
+            let method_signature = format!(
+                "{}{}",
+                current_class.get_utf8(&method_info.name_index)?,
+                current_class.get_utf8(&method_info.descriptor_index)?
+            );
             trace!("Method {} in {}", method_signature, class_name);
             result.insert(
                 method_signature.clone(),
-                Method::new(method_signature.clone()),
+                Method::new(
+                    method_signature,
+                    method_info.is_static(),
+                    method_info.is_abstract(),
+                ),
             );
         }
-        if let ConstPoolEntry::Class { name_index } =
-            current_class.const_pool[&current_class.super_class_idx]
-        {
-            let super_class_name = current_class.get_utf8(&name_index)?;
-            result.extend(collect_methods(super_class_name, classes, java_classes)?);
-            for iface_index in &current_class.iface_indexes {
-                let ConstPoolEntry::Class { name_index } = current_class.const_pool[iface_index]
-                else {
-                    continue;
-                };
-                result.extend(collect_methods(
-                    current_class.get_utf8(&name_index)?,
-                    classes,
-                    java_classes,
-                )?);
+        if let Some(super_class_name) = current_class.get_super_name()? {
+            inherit(
+                &mut result,
+                collect_methods(super_class_name, classes, java_classes)?,
+            );
+            for iface in current_class.get_interface_names()? {
+                inherit(&mut result, collect_methods(iface, classes, java_classes)?);
             }
         }
     } else if let Some(super_class) = java_classes.get(class_name) {
         trace!("Java Class {}", class_name);
         trace!("Java Class Methods: {:?}", super_class.methods);
-        result.extend(
-            super_class
-                .methods
-                .iter()
-                .map(|(sig, m)| (sig.clone(), m.clone())),
-        );
+        inherit(&mut result, super_class.methods.clone());
         if let Some(super_class) = super_class.super_class {
-            result.extend(collect_methods(super_class, classes, java_classes)?);
+            inherit(
+                &mut result,
+                collect_methods(super_class, classes, java_classes)?,
+            );
         }
         for iface in &super_class.interfaces {
-            result.extend(collect_methods(iface, classes, java_classes)?);
+            inherit(&mut result, collect_methods(iface, classes, java_classes)?);
+        }
+    }
+    Ok(result)
+}
+
+/// The superclasses of a class, nearest first, as far as they are found in `classes`.
+fn collect_superclasses<'a>(
+    class_name: &str,
+    classes: &'a HashMap<String, Class>,
+) -> Result<Vec<&'a str>, String> {
+    let mut result = Vec::new();
+    let mut current = classes.get(class_name);
+    while let Some(class) = current
+        && let Some(super_name) = class.get_super_name()?
+    {
+        //guards against a cycle in a broken class path
+        if result.contains(&super_name) {
+            break;
+        }
+        result.push(super_name);
+        current = classes.get(super_name);
+    }
+    Ok(result)
+}
+
+/// The declarations of the members of a class and of its supertypes found in `classes`. A
+/// declaration hides the inherited ones, superclasses come before superinterfaces.
+fn collect_declarations<'a>(
+    class_name: &str,
+    classes: &'a HashMap<String, Class>,
+) -> Result<HashMap<String, Declaration<'a>>, String> {
+    let mut result = HashMap::default();
+    let Some(class) = classes.get(class_name) else {
+        return Ok(result);
+    };
+    let name = class.get_name()?;
+    let nest_host = class.get_nest_host()?;
+    let declaration = |access, is_static| Declaration {
+        class: name,
+        nest_host,
+        access,
+        is_static,
+    };
+    for method in &class.methods {
+        let sig = format!(
+            "{}{}",
+            class.get_utf8(&method.name_index)?,
+            class.get_utf8(&method.descriptor_index)?
+        );
+        result.insert(sig, declaration(method.access(), method.is_static()));
+    }
+    for field in &class.fields {
+        let sig = format!(
+            "{}:{}",
+            class.get_utf8(&field.name_index)?,
+            class.get_utf8(&field.descriptor_index)?
+        );
+        result.insert(sig, declaration(field.access(), field.is_static()));
+    }
+    let supertypes = class.get_super_name()?.into_iter();
+    for supertype in supertypes.chain(class.get_interface_names()?) {
+        for (sig, inherited) in collect_declarations(supertype, classes)? {
+            result.entry(sig).or_insert(inherited);
         }
     }
     Ok(result)
 }
 
+/// A class or interface on the class path or in the JDK, with the methods an invocation may
+/// select.
+struct Supertype<'a> {
+    name: &'a str,
+    super_class: Option<&'a str>,
+    interfaces: Vec<&'a str>,
+    //neither static nor private methods, and whether they are abstract, None if unknown
+    methods: HashMap<String, Option<bool>>,
+}
+
+impl<'a> Supertype<'a> {
+    /// None if the class or one of its direct supertypes is neither on the class path nor in
+    /// the JDK.
+    fn find(
+        name: &str,
+        classes: &'a HashMap<String, Class>,
+        java_classes: &HashMap<&'a str, ClassInfo>,
+    ) -> Result<Option<Self>, String> {
+        if let Some((name, class)) = classes.get_key_value(name) {
+            let mut methods = HashMap::default();
+            for method in &class.methods {
+                if method.is_static() || method.is_private() {
+                    continue;
+                }
+                let sig = format!(
+                    "{}{}",
+                    class.get_utf8(&method.name_index)?,
+                    class.get_utf8(&method.descriptor_index)?
+                );
+                methods.insert(sig, Some(method.is_abstract()));
+            }
+            return Ok(Some(Supertype {
+                name,
+                super_class: class.get_super_name()?,
+                interfaces: class.get_interface_names()?,
+                methods,
+            }));
+        }
+        let Some((&name, class_info)) = java_classes.get_key_value(name) else {
+            return Ok(None);
+        };
+        let find_name = |name: &str| {
+            classes
+                .get_key_value(name)
+                .map(|(name, _)| name.as_str())
+                .or_else(|| java_classes.get_key_value(name).map(|(&name, _)| name))
+        };
+        let super_class = match class_info.super_class {
+            Some(super_class) => match find_name(super_class) {
+                Some(super_class) => Some(super_class),
+                None => return Ok(None),
+            },
+            None => None,
+        };
+        let Some(interfaces) = class_info.interfaces.iter().map(|i| find_name(i)).collect() else {
+            return Ok(None);
+        };
+        let methods = class_info
+            .methods
+            .iter()
+            .filter(|(_, m)| m.is_static != Some(true))
+            .map(|(sig, m)| (sig.clone(), m.is_abstract))
+            .collect();
+        Ok(Some(Supertype {
+            name,
+            super_class,
+            interfaces,
+            methods,
+        }))
+    }
+}
+
+/// The abstract methods a concrete class inherits without an implementation, with the
+/// supertype declaring them and whether it is an interface. Invoking them fails with an
+/// `AbstractMethodError`, see JVMS 5.4.6: a method declared by the class or a superclass is
+/// selected first, then the only non-abstract maximally-specific method of the
+/// superinterfaces, so default methods count as implementations. Classes with a supertype
+/// missing from the class path are skipped, the missing class is reported anyway.
+fn find_unimplemented<'a>(
+    class_name: &str,
+    classes: &'a HashMap<String, Class>,
+    java_classes: &HashMap<&'a str, ClassInfo>,
+) -> Result<Vec<(&'a str, bool, String)>, String> {
+    let Some(class) = classes.get(class_name) else {
+        return Ok(Vec::new());
+    };
+    if class.is_abstract() || class.is_interface() || class.is_module() {
+        return Ok(Vec::new());
+    }
+    let mut superclasses: Vec<Supertype> = Vec::new();
+    let mut next = Some(class_name);
+    while let Some(name) = next {
+        let Some(supertype) = Supertype::find(name, classes, java_classes)? else {
+            return Ok(Vec::new());
+        };
+        //guards against a cycle in a broken class path
+        if superclasses.iter().any(|s| s.name == supertype.name) {
+            return Ok(Vec::new());
+        }
+        next = supertype.super_class;
+        superclasses.push(supertype);
+    }
+    let mut interfaces: HashMap<&str, Supertype> = HashMap::default();
+    let mut pending: Vec<&str> = superclasses
+        .iter()
+        .flat_map(|s| s.interfaces.iter().copied())
+        .collect();
+    while let Some(name) = pending.pop() {
+        if interfaces.contains_key(name) {
+            continue;
+        }
+        let Some(interface) = Supertype::find(name, classes, java_classes)? else {
+            return Ok(Vec::new());
+        };
+        pending.extend(&interface.interfaces);
+        interfaces.insert(name, interface);
+    }
+
+    let mut result = Vec::new();
+    //the nearest declaration in the superclasses is selected, even if it is abstract
+    let mut selected = HashSet::default();
+    for superclass in &superclasses {
+        for (sig, is_abstract) in &superclass.methods {
+            if selected.insert(sig.as_str()) && *is_abstract == Some(true) {
+                result.push((superclass.name, false, sig.clone()));
+            }
+        }
+    }
+    let mut candidates: BTreeMap<&str, Vec<&Supertype>> = BTreeMap::new();
+    for interface in interfaces.values() {
+        for sig in interface.methods.keys() {
+            if !selected.contains(sig.as_str()) {
+                candidates.entry(sig).or_default().push(interface);
+            }
+        }
+    }
+    let extends = |interface: &Supertype, other: &str| {
+        let mut pending = interface.interfaces.clone();
+        let mut visited = HashSet::default();
+        while let Some(name) = pending.pop() {
+            if name == other {
+                return true;
+            }
+            if visited.insert(name) {
+                pending.extend(&interfaces[name].interfaces);
+            }
+        }
+        false
+    };
+    for (sig, declaring) in candidates {
+        let maximally_specific: Vec<&Supertype> = declaring
+            .iter()
+            .filter(|i| !declaring.iter().any(|other| extends(other, i.name)))
+            .copied()
+            .collect();
+        if maximally_specific
+            .iter()
+            .all(|i| i.methods[sig] == Some(true))
+        {
+            for interface in maximally_specific {
+                result.push((interface.name, true, sig.to_owned()));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The package of a class, empty for the unnamed package.
+fn package(class: &str) -> &str {
+    class.rsplit_once('/').map_or("", |(package, _)| package)
+}
+
+/// Adds the inherited methods not hidden by a declaration or by a method of a superclass.
+fn inherit(result: &mut HashMap<String, Method>, methods: HashMap<String, Method>) {
+    for (sig, method) in methods {
+        result.entry(sig).or_insert(method);
+    }
+}
+
+fn collect_fields(
+    class_name: &str,
+    classes: &HashMap<String, Class>,
+    java_classes: &HashMap<&str, ClassInfo<'_>>,
+) -> Result<Option<HashSet<String>>, String> {
+    let mut result = HashSet::default();
+    if let Some(current_class) = classes.get(class_name) {
+        trace!("Class {}", class_name);
+        result.extend(current_class.get_fields()?);
+        if let Some(super_class_name) = current_class.get_super_name()? {
+            let mut supertypes = vec![super_class_name];
+            supertypes.extend(current_class.get_interface_names()?);
+            for supertype in supertypes {
+                let Some(fields) = collect_fields(supertype, classes, java_classes)? else {
+                    return Ok(None);
+                };
+                result.extend(fields);
+            }
+        }
+    } else if let Some(super_class) = java_classes.get(class_name) {
+        trace!("Java Class {}", class_name);
+        let Some(fields) = &super_class.fields else {
+            return Ok(None);
+        };
+        result.extend(fields.iter().cloned());
+        for supertype in super_class
+            .super_class
+            .iter()
+            .chain(&super_class.interfaces)
+        {
+            let Some(fields) = collect_fields(supertype, classes, java_classes)? else {
+                return Ok(None);
+            };
+            result.extend(fields);
+        }
+    }
+    Ok(Some(result))
+}
+
+/// The unmet dependencies of the classes, and the classes that could not be checked, e.g. as
+/// their constant pool is broken. These are skipped, like classes that cannot be read.
 pub fn check_classes<'a>(
     classes: &'a HashMap<String, Class>,
     parallel: bool,
     java_classes: &HashMap<&'a str, ClassInfo>,
-) -> Option<HashSet<ClassRequirements<'a>>> {
+) -> (HashSet<ClassRequirements<'a>>, Vec<InvalidClass>) {
     info!("Checking class dependencies");
-    let provided = get_provided(classes, parallel, java_classes);
+    let (provided, mut invalid) = get_provided(classes, parallel, java_classes);
+    let (consumed, invalid_consumers) = get_consumed(classes, parallel);
+    invalid.extend(invalid_consumers);
     let mut dependencies: Vec<ClassRequirements<'a>> = Vec::new();
-    dependencies.extend(get_consumed(classes, parallel));
-    for dep in dependencies.iter_mut() {
-        dep.remove_java_classes_and_methods(java_classes);
-    }
+    dependencies.extend(consumed);
+    dependencies.retain_mut(|dep| match collect_superclasses(dep.name, classes) {
+        Ok(superclasses) => {
+            dep.superclasses = superclasses;
+            dep.remove_java_classes_and_methods(java_classes);
+            true
+        }
+        Err(cause) => {
+            invalid.push(InvalidClass::new(dep.name, cause));
+            false
+        }
+    });
     debug!(
         "Provided size {} | Dependencies count {}",
         provided.capacity(),
@@ -465,90 +1281,108 @@ pub fn check_classes<'a>(
     );
     if parallel {
         dependencies.par_iter_mut().for_each(|dep| {
-            for (class, method_provider) in &provided {
+            for (class, member_provider) in &provided {
                 if dep.dependencies.contains_key(class) {
-                    dep.remove_methods(class, &method_provider);
-                    dep.remove_class(class, method_provider.interface);
+                    dep.remove_members(class, member_provider);
+                    dep.remove_class(class, member_provider.interface, member_provider.public);
                 }
                 dep.clear_empty_deps();
             }
         });
     } else {
         for dep in dependencies.iter_mut() {
-            for (class, method_provider) in &provided {
+            for (class, member_provider) in &provided {
                 if dep.dependencies.contains_key(class) {
-                    dep.remove_methods(class, &method_provider);
-                    dep.remove_class(class, method_provider.interface);
+                    dep.remove_members(class, member_provider);
+                    dep.remove_class(class, member_provider.interface, member_provider.public);
                 }
                 dep.clear_empty_deps();
             }
         }
     }
-    dependencies.retain(|dep| !dep.is_empty());
+    let add_unimplemented = |dep: &mut ClassRequirements<'a>| {
+        dep.add_unimplemented(classes, java_classes)
+            .map_err(|cause| InvalidClass::new(dep.name, cause))
+            .err()
+    };
+    let invalid_subclasses: Vec<InvalidClass> = if parallel {
+        dependencies
+            .par_iter_mut()
+            .filter_map(add_unimplemented)
+            .collect()
+    } else {
+        dependencies
+            .iter_mut()
+            .filter_map(add_unimplemented)
+            .collect()
+    };
+    dependencies
+        .retain(|dep| !dep.is_empty() && !invalid_subclasses.iter().any(|i| i.class == dep.name));
+    invalid.extend(invalid_subclasses);
+    //a class may fail both as consumer and as provider
+    invalid.sort_by(|a, b| a.class.cmp(&b.class));
+    invalid.dedup_by(|a, b| a.class == b.class);
     let mut result = HashSet::default();
     result.extend(dependencies);
     info!(
-        "Finished. Classes with unmet dependencies: {}",
-        result.len()
+        "Finished. Classes with unmet dependencies: {}, invalid classes: {}",
+        result.len(),
+        invalid.len()
     );
-    Some(result)
+    (result, invalid)
 }
 
-fn get_consumed(
-    classes: &HashMap<String, Class>,
+/// Splits the results for every class into the values and the classes that failed.
+fn split_invalid<T>(results: Vec<Result<T, InvalidClass>>) -> (Vec<T>, Vec<InvalidClass>) {
+    let mut values = Vec::with_capacity(results.len());
+    let mut invalid = Vec::new();
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(e) => invalid.push(e),
+        }
+    }
+    (values, invalid)
+}
+
+fn get_consumed<'a>(
+    classes: &'a HashMap<String, Class>,
     parallel: bool,
-) -> HashSet<ClassRequirements<'_>> {
-    if parallel {
-        classes
-            .par_iter()
-            .map(|(_, class)| class.get_consumed().unwrap())
-            .fold(HashSet::default, |mut a, b| {
-                a.insert(b);
-                a
-            })
-            .reduce(HashSet::default, |mut a, b| {
-                a.extend(b);
-                a
-            })
+) -> (HashSet<ClassRequirements<'a>>, Vec<InvalidClass>) {
+    let consume = |(name, class): (&String, &'a Class)| {
+        class
+            .get_consumed()
+            .map_err(|cause| InvalidClass::new(name, cause))
+    };
+    let results = if parallel {
+        classes.par_iter().map(consume).collect()
     } else {
-        classes
-            .values()
-            .map(|class| class.get_consumed().unwrap())
-            .fold(HashSet::default(), |mut a, b| {
-                a.insert(b);
-                a
-            })
-    }
+        classes.iter().map(consume).collect()
+    };
+    let (consumed, invalid) = split_invalid(results);
+    (consumed.into_iter().collect(), invalid)
 }
 
 fn get_provided<'a>(
     classes: &'a HashMap<String, Class>,
     parallel: bool,
     java_classes: &HashMap<&str, ClassInfo>,
-) -> HashMap<&'a str, MethodProvider<'a>> {
-    if parallel {
-        classes
-            .par_iter()
-            .map(|(_, class)| class.get_provided(classes, java_classes).unwrap())
-            .filter(|opt| opt.is_some())
-            .map(|opt| opt.unwrap())
-            .fold(HashMap::default, |mut a, b| {
-                a.insert(b.name, b);
-                a
-            })
-            .reduce(HashMap::default, |mut a, b| {
-                b.into_iter().for_each(|(k, v)| {
-                    a.insert(k, v);
-                });
-                a
-            })
+) -> (HashMap<&'a str, MemberProvider<'a>>, Vec<InvalidClass>) {
+    let provide = |(name, class): (&String, &'a Class)| {
+        class
+            .get_provided(classes, java_classes)
+            .map_err(|cause| InvalidClass::new(name, cause))
+    };
+    let results = if parallel {
+        classes.par_iter().map(provide).collect()
     } else {
-        classes
-            .values()
-            .filter_map(|class| class.get_provided(classes, java_classes).unwrap())
-            .fold(HashMap::default(), |mut a, b| {
-                a.insert(b.name, b);
-                a
-            })
-    }
+        classes.iter().map(provide).collect()
+    };
+    let (provided, invalid) = split_invalid(results);
+    let provided = provided
+        .into_iter()
+        .flatten()
+        .map(|provider| (provider.name, provider))
+        .collect();
+    (provided, invalid)
 }
@@ -0,0 +1,580 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use ahash::{AHashMap, AHashSet};
+use base64::{Engine, prelude::BASE64_STANDARD};
+use cms::{
+    cert::CertificateChoices,
+    content_info::ContentInfo,
+    signed_data::{SignedData, SignerIdentifier, SignerInfo},
+};
+use const_oid::db::{rfc5280, rfc5911, rfc5912};
+use java_class::{
+    manifest::{MANIFEST_PATH, Manifest, Section},
+    read_jar_entries,
+};
+use log::{debug, info, trace};
+use rayon::prelude::*;
+use rsa::{Pkcs1v15Sign, RsaPublicKey, pkcs8::DecodePublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use signature::hazmat::PrehashVerifier;
+use x509_cert::{
+    Certificate,
+    der::{Decode, Encode, asn1::ObjectIdentifier, asn1::OctetString},
+    ext::pkix::SubjectKeyIdentifier,
+    spki::SubjectPublicKeyInfoOwned,
+};
+
+type HashMap<K, V> = AHashMap<K, V>;
+type HashSet<E> = AHashSet<E>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JarStatus {
+    Unsigned,
+    Signed,
+    PartiallySigned,
+    Tampered,
+}
+
+impl Display for JarStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JarStatus::Unsigned => write!(f, "Unsigned"),
+            JarStatus::Signed => write!(f, "Signed"),
+            JarStatus::PartiallySigned => write!(f, "Partially signed"),
+            JarStatus::Tampered => write!(f, "Tampered"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Signer {
+    pub name: String,
+    pub subject: Option<String>,
+    pub problem: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EntryFinding {
+    Tampered { entry: String, reason: String },
+    Missing(String),
+    Unsigned(String),
+}
+
+#[derive(Debug)]
+pub struct SignatureReport {
+    pub path: PathBuf,
+    pub signers: Vec<Signer>,
+    pub findings: Vec<EntryFinding>,
+    pub signed_entries: usize,
+}
+
+impl SignatureReport {
+    pub fn status(&self) -> JarStatus {
+        if self.signers.is_empty() {
+            return JarStatus::Unsigned;
+        }
+        if self.signers.iter().any(|s| s.problem.is_some())
+            || self
+                .findings
+                .iter()
+                .any(|f| !matches!(f, EntryFinding::Unsigned(_)))
+        {
+            return JarStatus::Tampered;
+        }
+        if self.findings.is_empty() {
+            JarStatus::Signed
+        } else {
+            JarStatus::PartiallySigned
+        }
+    }
+
+    pub fn format(&self) -> String {
+        let mut result = format!("{}: {}\n", self.path.display(), self.status());
+        for signer in &self.signers {
+            result.push_str(format!("\tSigner {}", signer.name).as_str());
+            if let Some(subject) = &signer.subject {
+                result.push_str(format!(" ({subject})").as_str());
+            }
+            match &signer.problem {
+                Some(problem) => result.push_str(format!(": Invalid, {problem}\n").as_str()),
+                None => result.push_str(": Valid\n"),
+            }
+        }
+        for finding in &self.findings {
+            match finding {
+                EntryFinding::Tampered { entry, reason } => {
+                    result.push_str(format!("\tTampered entry {entry} ({reason})\n").as_str())
+                }
+                EntryFinding::Missing(entry) => {
+                    result.push_str(format!("\tMissing entry {entry}\n").as_str())
+                }
+                EntryFinding::Unsigned(entry) => {
+                    result.push_str(format!("\tUnsigned entry {entry}\n").as_str())
+                }
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DigestAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    fn from_manifest_name(name: &str) -> Option<Self> {
+        match name.replace('-', "").to_ascii_uppercase().as_str() {
+            "SHA1" => Some(DigestAlgorithm::Sha1),
+            "SHA256" => Some(DigestAlgorithm::Sha256),
+            "SHA384" => Some(DigestAlgorithm::Sha384),
+            "SHA512" => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    fn from_oid(oid: &ObjectIdentifier) -> Option<Self> {
+        match *oid {
+            rfc5912::ID_SHA_1 => Some(DigestAlgorithm::Sha1),
+            rfc5912::ID_SHA_256 => Some(DigestAlgorithm::Sha256),
+            rfc5912::ID_SHA_384 => Some(DigestAlgorithm::Sha384),
+            rfc5912::ID_SHA_512 => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    fn digest<'a>(&self, parts: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+        match self {
+            DigestAlgorithm::Sha1 => hash::<Sha1>(parts),
+            DigestAlgorithm::Sha256 => hash::<Sha256>(parts),
+            DigestAlgorithm::Sha384 => hash::<Sha384>(parts),
+            DigestAlgorithm::Sha512 => hash::<Sha512>(parts),
+        }
+    }
+
+    fn pkcs1v15(&self) -> Pkcs1v15Sign {
+        match self {
+            DigestAlgorithm::Sha1 => Pkcs1v15Sign::new::<Sha1>(),
+            DigestAlgorithm::Sha256 => Pkcs1v15Sign::new::<Sha256>(),
+            DigestAlgorithm::Sha384 => Pkcs1v15Sign::new::<Sha384>(),
+            DigestAlgorithm::Sha512 => Pkcs1v15Sign::new::<Sha512>(),
+        }
+    }
+}
+
+fn hash<'a, D: Digest>(parts: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut hasher = D::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().to_vec()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DigestCheck {
+    Absent,
+    Unsupported,
+    Match,
+    Mismatch(String),
+}
+
+/// Compares all `<ALG><suffix>` attributes of a section against the digest of `data`.
+fn check_digests(section: &Section, suffix: &str, data: &[&[u8]]) -> DigestCheck {
+    let mut result = DigestCheck::Absent;
+    for (key, value) in section.attributes() {
+        let Some(alg_name) = key
+            .len()
+            .checked_sub(suffix.len())
+            .filter(|&idx| key.is_char_boundary(idx) && key[idx..].eq_ignore_ascii_case(suffix))
+            .map(|idx| &key[..idx])
+        else {
+            continue;
+        };
+        let Some(alg) = DigestAlgorithm::from_manifest_name(alg_name) else {
+            trace!("Unsupported digest algorithm {}", alg_name);
+            if result == DigestCheck::Absent {
+                result = DigestCheck::Unsupported;
+            }
+            continue;
+        };
+        let expected = BASE64_STANDARD.decode(value.trim()).unwrap_or_default();
+        if expected != alg.digest(data.iter().copied()) {
+            return DigestCheck::Mismatch(alg_name.to_owned());
+        }
+        result = DigestCheck::Match;
+    }
+    result
+}
+
+fn is_signature_related(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    let Some(file) = upper.strip_prefix("META-INF/") else {
+        return false;
+    };
+    !file.contains('/')
+        && (file == "MANIFEST.MF"
+            || file.ends_with(".SF")
+            || file.ends_with(".RSA")
+            || file.ends_with(".DSA")
+            || file.ends_with(".EC")
+            || file.starts_with("SIG-"))
+}
+
+struct SignerResult {
+    signer: Signer,
+    covered: Vec<String>,
+    findings: Vec<EntryFinding>,
+}
+
+pub fn verify_jar(path: &Path) -> Result<SignatureReport, java_class::error::Error> {
+    info!("Verifying signatures of {}", path.display());
+    let entries = read_jar_entries(path)?;
+    let lookup: HashMap<&str, &[u8]> = entries
+        .iter()
+        .map(|(name, data)| (name.as_str(), data.as_slice()))
+        .collect();
+    let mut report = SignatureReport {
+        path: path.to_owned(),
+        signers: vec![],
+        findings: vec![],
+        signed_entries: 0,
+    };
+
+    let signature_files: Vec<&str> = entries
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .filter(|name| {
+            name.strip_prefix("META-INF/")
+                .is_some_and(|f| !f.contains('/') && f.to_ascii_uppercase().ends_with(".SF"))
+        })
+        .collect();
+    if signature_files.is_empty() {
+        debug!("{} is not signed", path.display());
+        return Ok(report);
+    }
+
+    let manifest = match lookup.get(MANIFEST_PATH) {
+        Some(data) => Manifest::parse(data.to_vec()),
+        None => Err("JAR has no manifest".to_owned()),
+    };
+    let mut covered = HashSet::default();
+    for sf_name in signature_files {
+        let result = verify_signer(sf_name, &lookup, manifest.as_ref());
+        if result.signer.problem.is_none() {
+            covered.extend(result.covered);
+        }
+        report.findings.extend(result.findings);
+        report.signers.push(result.signer);
+    }
+
+    if let Ok(manifest) = &manifest {
+        for (name, data) in &entries {
+            if is_signature_related(name) {
+                continue;
+            }
+            let digest_check = match manifest.section(name) {
+                Some(section) => check_digests(section, "-Digest", &[data]),
+                None => DigestCheck::Absent,
+            };
+            match digest_check {
+                DigestCheck::Mismatch(alg) => report.findings.push(EntryFinding::Tampered {
+                    entry: name.to_owned(),
+                    reason: format!("{alg} digest mismatch"),
+                }),
+                DigestCheck::Match if covered.contains(name) => report.signed_entries += 1,
+                _ => report
+                    .findings
+                    .push(EntryFinding::Unsigned(name.to_owned())),
+            }
+        }
+        for section in manifest.sections() {
+            if let Some(name) = &section.name
+                && covered.contains(name)
+                && !lookup.contains_key(name.as_str())
+            {
+                report.findings.push(EntryFinding::Missing(name.to_owned()));
+            }
+        }
+    }
+    report.findings.sort();
+    report.findings.dedup();
+    debug!(
+        "{}: {} signed entries, {} findings",
+        path.display(),
+        report.signed_entries,
+        report.findings.len()
+    );
+    Ok(report)
+}
+
+fn verify_signer(
+    sf_name: &str,
+    entries: &HashMap<&str, &[u8]>,
+    manifest: Result<&Manifest, &String>,
+) -> SignerResult {
+    let base_name = &sf_name[..sf_name.len() - ".SF".len()];
+    let mut result = SignerResult {
+        signer: Signer {
+            name: base_name.trim_start_matches("META-INF/").to_owned(),
+            subject: None,
+            problem: None,
+        },
+        covered: vec![],
+        findings: vec![],
+    };
+    let sf_data = entries[sf_name];
+    let block = ["RSA", "DSA", "EC"]
+        .iter()
+        .find_map(|ext| entries.get(format!("{base_name}.{ext}").as_str()));
+    let Some(block) = block else {
+        result.signer.problem = Some("no signature block file".to_owned());
+        return result;
+    };
+    match verify_signature_block(block, sf_data) {
+        Ok(subject) => result.signer.subject = Some(subject),
+        Err(e) => {
+            result.signer.problem = Some(e);
+            return result;
+        }
+    }
+
+    let manifest = match manifest {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            result.signer.problem = Some(e.to_owned());
+            return result;
+        }
+    };
+    let signature_file = match Manifest::parse(sf_data.to_vec()) {
+        Ok(sf) => sf,
+        Err(e) => {
+            result.signer.problem = Some(format!("malformed signature file: {e}"));
+            return result;
+        }
+    };
+    let sf_main = signature_file.main_section();
+    let manifest_signed =
+        check_digests(sf_main, "-Digest-Manifest", &[manifest.raw()]) == DigestCheck::Match;
+    if !manifest_signed {
+        let main_parts: Vec<&[u8]> = manifest.raw_parts(manifest.main_section()).collect();
+        if let DigestCheck::Mismatch(_) =
+            check_digests(sf_main, "-Digest-Manifest-Main-Attributes", &main_parts)
+        {
+            result.signer.problem =
+                Some("manifest main attributes were modified after signing".to_owned());
+            return result;
+        }
+    }
+
+    for section in signature_file.sections() {
+        let Some(name) = &section.name else {
+            continue;
+        };
+        if manifest_signed {
+            result.covered.push(name.to_owned());
+            continue;
+        }
+        let Some(manifest_section) = manifest.section(name) else {
+            result.findings.push(EntryFinding::Tampered {
+                entry: name.to_owned(),
+                reason: "manifest section was removed after signing".to_owned(),
+            });
+            continue;
+        };
+        let parts: Vec<&[u8]> = manifest.raw_parts(manifest_section).collect();
+        match check_digests(section, "-Digest", &parts) {
+            DigestCheck::Match => result.covered.push(name.to_owned()),
+            DigestCheck::Mismatch(_) => result.findings.push(EntryFinding::Tampered {
+                entry: name.to_owned(),
+                reason: "manifest section was modified after signing".to_owned(),
+            }),
+            _ => (),
+        }
+    }
+    result
+}
+
+/// Verifies a PKCS#7 signature block over the detached signature file and
+/// returns the subject of the signer certificate.
+fn verify_signature_block(block: &[u8], content: &[u8]) -> Result<String, String> {
+    let content_info =
+        ContentInfo::from_der(block).map_err(|e| format!("malformed signature block: {e}"))?;
+    if content_info.content_type != rfc5911::ID_SIGNED_DATA {
+        return Err(format!(
+            "unexpected signature block content type {}",
+            content_info.content_type
+        ));
+    }
+    let signed_data: SignedData = content_info
+        .content
+        .decode_as()
+        .map_err(|e| format!("malformed signed data: {e}"))?;
+    let certificates: Vec<&Certificate> = signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(cert) => Some(cert),
+            _ => None,
+        })
+        .collect();
+    let mut subject = None;
+    for signer_info in signed_data.signer_infos.0.iter() {
+        let cert = verify_signer_info(signer_info, &certificates, content)?;
+        subject.get_or_insert_with(|| cert.tbs_certificate.subject.to_string());
+    }
+    subject.ok_or_else(|| "signature block contains no signer".to_owned())
+}
+
+fn find_certificate<'a>(
+    sid: &SignerIdentifier,
+    certificates: &[&'a Certificate],
+) -> Option<&'a Certificate> {
+    certificates.iter().copied().find(|cert| match sid {
+        SignerIdentifier::IssuerAndSerialNumber(ias) => {
+            cert.tbs_certificate.issuer == ias.issuer
+                && cert.tbs_certificate.serial_number == ias.serial_number
+        }
+        SignerIdentifier::SubjectKeyIdentifier(ski) => cert
+            .tbs_certificate
+            .extensions
+            .iter()
+            .flatten()
+            .filter(|ext| ext.extn_id == rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER)
+            .any(|ext| {
+                SubjectKeyIdentifier::from_der(ext.extn_value.as_bytes()).as_ref() == Ok(ski)
+            }),
+    })
+}
+
+fn verify_signer_info<'a>(
+    signer_info: &SignerInfo,
+    certificates: &[&'a Certificate],
+    content: &[u8],
+) -> Result<&'a Certificate, String> {
+    let cert = find_certificate(&signer_info.sid, certificates)
+        .ok_or_else(|| "signer certificate not found in signature block".to_owned())?;
+    let digest_alg = DigestAlgorithm::from_oid(&signer_info.digest_alg.oid).ok_or_else(|| {
+        format!(
+            "unsupported digest algorithm {}",
+            signer_info.digest_alg.oid
+        )
+    })?;
+    let signed_message = match &signer_info.signed_attrs {
+        Some(attrs) => {
+            let message_digest = attrs
+                .iter()
+                .find(|attr| attr.oid == rfc5911::ID_MESSAGE_DIGEST)
+                .and_then(|attr| attr.values.iter().next())
+                .ok_or_else(|| "signed attributes lack a message digest".to_owned())?
+                .decode_as::<OctetString>()
+                .map_err(|e| format!("malformed message digest: {e}"))?;
+            if message_digest.as_bytes() != digest_alg.digest([content]) {
+                return Err("signature file was modified after signing".to_owned());
+            }
+            attrs
+                .to_der()
+                .map_err(|e| format!("malformed signed attributes: {e}"))?
+        }
+        None => content.to_vec(),
+    };
+    let hashed = digest_alg.digest([signed_message.as_slice()]);
+    verify_hash(
+        &cert.tbs_certificate.subject_public_key_info,
+        digest_alg,
+        &hashed,
+        signer_info.signature.as_bytes(),
+    )?;
+    Ok(cert)
+}
+
+fn verify_hash(
+    key_info: &SubjectPublicKeyInfoOwned,
+    digest_alg: DigestAlgorithm,
+    hashed: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let key_der = key_info
+        .to_der()
+        .map_err(|e| format!("malformed public key: {e}"))?;
+    let key_error = |e: rsa::pkcs8::spki::Error| format!("malformed public key: {e}");
+    let invalid = |_| "signature does not match the signer certificate".to_owned();
+    match key_info.algorithm.oid {
+        rfc5912::RSA_ENCRYPTION => RsaPublicKey::from_public_key_der(&key_der)
+            .map_err(key_error)?
+            .verify(digest_alg.pkcs1v15(), hashed, signature)
+            .map_err(|_| "signature does not match the signer certificate".to_owned()),
+        rfc5912::ID_EC_PUBLIC_KEY => {
+            let curve = key_info
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.decode_as::<ObjectIdentifier>().ok());
+            match curve {
+                Some(rfc5912::SECP_256_R_1) => {
+                    let signature = p256::ecdsa::DerSignature::from_bytes(signature)
+                        .map_err(|e| format!("malformed signature: {e}"))?;
+                    p256::ecdsa::VerifyingKey::from_public_key_der(&key_der)
+                        .map_err(key_error)?
+                        .verify_prehash(hashed, &signature)
+                        .map_err(invalid)
+                }
+                Some(rfc5912::SECP_384_R_1) => {
+                    let signature = p384::ecdsa::DerSignature::from_bytes(signature)
+                        .map_err(|e| format!("malformed signature: {e}"))?;
+                    p384::ecdsa::VerifyingKey::from_public_key_der(&key_der)
+                        .map_err(key_error)?
+                        .verify_prehash(hashed, &signature)
+                        .map_err(invalid)
+                }
+                _ => Err("unsupported elliptic curve".to_owned()),
+            }
+        }
+        rfc5912::ID_DSA => {
+            let signature = dsa::Signature::try_from(signature)
+                .map_err(|e| format!("malformed signature: {e}"))?;
+            dsa::VerifyingKey::from_public_key_der(&key_der)
+                .map_err(key_error)?
+                .verify_prehash(hashed, &signature)
+                .map_err(invalid)
+        }
+        oid => Err(format!("unsupported key algorithm {oid}")),
+    }
+}
+
+pub fn verify_jars(
+    paths: &[PathBuf],
+    parallel: bool,
+) -> Result<Vec<SignatureReport>, java_class::error::Error> {
+    info!("Verifying signatures of {} JAR files", paths.len());
+    let result: Result<Vec<SignatureReport>, _> = if parallel {
+        paths.par_iter().map(|p| verify_jar(p)).collect()
+    } else {
+        paths.iter().map(|p| verify_jar(p)).collect()
+    };
+    if let Ok(reports) = &result {
+        info!(
+            "Finished. JARs not fully signed: {}",
+            reports
+                .iter()
+                .filter(|r| r.status() != JarStatus::Signed)
+                .count()
+        );
+    }
+    result
+}
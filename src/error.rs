@@ -25,6 +25,62 @@ impl Display for ArgError {
 
 impl std::error::Error for ArgError {}
 
+#[derive(Debug)]
+pub enum SuppressionError {
+    //the index of an entry without any pattern
+    MatchesEverything(usize),
+    InvalidDate(String),
+}
+
+impl Display for SuppressionError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            SuppressionError::MatchesEverything(index) => write!(
+                f,
+                "{}: entry {} has no consumer, dependency or member",
+                stringify!(SuppressionError::MatchesEverything),
+                index
+            ),
+            SuppressionError::InvalidDate(ref date) => write!(
+                f,
+                "{}: {} is not a YYYY-MM-DD date",
+                stringify!(SuppressionError::InvalidDate),
+                date
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SuppressionError {}
+
+#[derive(Debug)]
+pub enum BaselineError {
+    UnsupportedSchema(u32),
+    //the number of a line that is not part of a text report
+    InvalidLine(usize),
+}
+
+impl Display for BaselineError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match *self {
+            BaselineError::UnsupportedSchema(version) => write!(
+                f,
+                "{}: schema version {} is not supported",
+                stringify!(BaselineError::UnsupportedSchema),
+                version
+            ),
+            BaselineError::InvalidLine(line) => write!(
+                f,
+                "{}: line {} is not part of a report",
+                stringify!(BaselineError::InvalidLine),
+                line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BaselineError {}
+
 macro_rules! define_errcodes {
     [ $( $name:ident : $class:ty ),+ ] => {
         #[derive(Debug)]
@@ -67,5 +123,9 @@ define_errcodes![
     Io: std::io::Error,
     Parsing: java_class::error::Error,
     Threading: rayon::ThreadPoolBuildError,
-    Args: ArgError
+    Args: ArgError,
+    Json: serde_json::Error,
+    Suppression: SuppressionError,
+    Baseline: BaselineError,
+    Api: java_class::error::InvalidClass
 ];
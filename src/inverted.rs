@@ -0,0 +1,322 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use java_class::{ClassOrigin, java_class::Access};
+use reference_checker::{ClassRequirements, DependencyKind, DependencyStatus};
+use serde::Serialize;
+
+use crate::HashMap;
+
+/// A class with an unmet dependency and the archive it was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Consumer<'a> {
+    pub name: &'a str,
+    pub archive: Option<&'a Path>,
+}
+
+/// The consumers of one required class.
+#[derive(Debug, Serialize)]
+pub struct Dependents<'a> {
+    pub name: &'a str,
+    pub kind: DependencyKind,
+    pub status: DependencyStatus,
+    //every class with an unmet dependency on this one, and the archives of those classes
+    pub consumer_count: usize,
+    pub archive_count: usize,
+    //the consumers of a missing class or a class of the other kind
+    pub consumers: BTreeSet<Consumer<'a>>,
+    //the consumers of every missing member of a found class
+    pub methods: BTreeMap<&'a str, BTreeSet<Consumer<'a>>>,
+    pub fields: BTreeMap<&'a str, BTreeSet<Consumer<'a>>>,
+    pub static_mismatches: BTreeMap<&'a str, StaticMismatchConsumers<'a>>,
+    pub inaccessible_members: BTreeMap<&'a str, InaccessibleConsumers<'a>>,
+    pub unimplemented_methods: BTreeMap<&'a str, BTreeSet<Consumer<'a>>>,
+}
+
+/// The consumers of a method that is static where an instance method is required or vice versa.
+#[derive(Debug, Serialize)]
+pub struct StaticMismatchConsumers<'a> {
+    pub is_static: bool,
+    pub consumers: BTreeSet<Consumer<'a>>,
+}
+
+/// The consumers of a method or field they may not access.
+#[derive(Debug, Serialize)]
+pub struct InaccessibleConsumers<'a> {
+    pub access: Access,
+    pub consumers: BTreeSet<Consumer<'a>>,
+}
+
+/// The missing classes of a package, which are usually provided by the same JAR.
+#[derive(Debug, Serialize)]
+pub struct MissingPackage<'a> {
+    pub name: &'a str,
+    pub classes: usize,
+    pub consumer_count: usize,
+    pub archive_count: usize,
+}
+
+/// The requirements keyed by the required classes, the ones with the most consumers first.
+#[derive(Debug, Serialize)]
+pub struct Inverted<'a> {
+    pub dependents: Vec<Dependents<'a>>,
+    pub missing_packages: Vec<MissingPackage<'a>>,
+}
+
+impl<'a> Inverted<'a> {
+    pub fn new(
+        requirements: &'a [ClassRequirements<'a>],
+        origins: &'a HashMap<String, ClassOrigin>,
+    ) -> Self {
+        let mut by_name: BTreeMap<&str, Dependents> = BTreeMap::new();
+        for r in requirements {
+            let consumer = Consumer {
+                name: r.name(),
+                archive: origins.get(r.name()).map(|o| o.archive.as_path()),
+            };
+            for dependency in r.dependencies() {
+                let dependents = by_name.entry(dependency.name).or_insert(Dependents {
+                    name: dependency.name,
+                    kind: dependency.kind,
+                    status: dependency.status,
+                    consumer_count: 0,
+                    archive_count: 0,
+                    consumers: BTreeSet::new(),
+                    methods: BTreeMap::new(),
+                    fields: BTreeMap::new(),
+                    static_mismatches: BTreeMap::new(),
+                    inaccessible_members: BTreeMap::new(),
+                    unimplemented_methods: BTreeMap::new(),
+                });
+                //consumers may disagree on whether the class is an interface, and only some may
+                //access it
+                if matches!(
+                    dependency.status,
+                    DependencyStatus::KindMismatch | DependencyStatus::Inaccessible
+                ) {
+                    dependents.status = dependency.status;
+                }
+                if dependency.status == DependencyStatus::ApiMismatch {
+                    for method in dependency.methods {
+                        dependents
+                            .methods
+                            .entry(method)
+                            .or_default()
+                            .insert(consumer);
+                    }
+                    for field in dependency.fields {
+                        dependents.fields.entry(field).or_default().insert(consumer);
+                    }
+                    for mismatch in dependency.static_mismatches {
+                        dependents
+                            .static_mismatches
+                            .entry(mismatch.method)
+                            .or_insert_with(|| StaticMismatchConsumers {
+                                is_static: mismatch.is_static,
+                                consumers: BTreeSet::new(),
+                            })
+                            .consumers
+                            .insert(consumer);
+                    }
+                    for inaccessible in dependency.inaccessible_members {
+                        dependents
+                            .inaccessible_members
+                            .entry(inaccessible.member)
+                            .or_insert_with(|| InaccessibleConsumers {
+                                access: inaccessible.access,
+                                consumers: BTreeSet::new(),
+                            })
+                            .consumers
+                            .insert(consumer);
+                    }
+                    for method in dependency.unimplemented_methods {
+                        dependents
+                            .unimplemented_methods
+                            .entry(method)
+                            .or_default()
+                            .insert(consumer);
+                    }
+                } else {
+                    dependents.consumers.insert(consumer);
+                }
+            }
+        }
+
+        let mut dependents: Vec<Dependents> = by_name.into_values().collect();
+        for d in &mut dependents {
+            let consumers = d.all_consumers();
+            d.consumer_count = consumers.len();
+            d.archive_count = archives(&consumers).len();
+        }
+        dependents.sort_by(|a, b| {
+            b.consumer_count
+                .cmp(&a.consumer_count)
+                .then(a.name.cmp(b.name))
+        });
+
+        let mut packages: BTreeMap<&str, (usize, BTreeSet<Consumer>)> = BTreeMap::new();
+        for d in &dependents {
+            if d.status != DependencyStatus::Missing {
+                continue;
+            }
+            let package = d.name.rsplit_once('/').map_or("", |(package, _)| package);
+            let (classes, consumers) = packages.entry(package).or_default();
+            *classes += 1;
+            consumers.extend(&d.consumers);
+        }
+        let mut missing_packages: Vec<MissingPackage> = packages
+            .into_iter()
+            .map(|(name, (classes, consumers))| MissingPackage {
+                name,
+                classes,
+                consumer_count: consumers.len(),
+                archive_count: archives(&consumers).len(),
+            })
+            .collect();
+        missing_packages.sort_by(|a, b| {
+            b.consumer_count
+                .cmp(&a.consumer_count)
+                .then(a.name.cmp(b.name))
+        });
+
+        Inverted {
+            dependents,
+            missing_packages,
+        }
+    }
+
+    pub fn format(&self) -> String {
+        let mut result = String::new();
+        for d in &self.dependents {
+            let kind = match d.kind {
+                DependencyKind::Class => "Class",
+                DependencyKind::Interface => "Interface",
+            };
+            let status = match d.status {
+                DependencyStatus::Missing => "missing",
+                DependencyStatus::ApiMismatch => "API mismatch",
+                DependencyStatus::KindMismatch => "class/interface mismatch",
+                DependencyStatus::Inaccessible => "inaccessible",
+            };
+            result.push_str(
+                format!(
+                    "{kind} {} ({status}), consumers: {}, archives: {}\n",
+                    d.name, d.consumer_count, d.archive_count
+                )
+                .as_str(),
+            );
+            for consumer in &d.consumers {
+                result.push_str(format!("\t{}\n", format_consumer(consumer)).as_str());
+            }
+            let members = d.methods.iter().map(|(m, c)| ("Method", m, c));
+            for (kind, member, consumers) in
+                members.chain(d.fields.iter().map(|(f, c)| ("Field", f, c)))
+            {
+                result.push_str(
+                    format!("\t{kind} {member}, consumers: {}\n", consumers.len()).as_str(),
+                );
+                for consumer in consumers {
+                    result.push_str(format!("\t\t{}\n", format_consumer(consumer)).as_str());
+                }
+            }
+            for (method, mismatch) in &d.static_mismatches {
+                result.push_str(
+                    format!(
+                        "\tMethod {method} is {}static, consumers: {}\n",
+                        if mismatch.is_static { "" } else { "not " },
+                        mismatch.consumers.len()
+                    )
+                    .as_str(),
+                );
+                for consumer in &mismatch.consumers {
+                    result.push_str(format!("\t\t{}\n", format_consumer(consumer)).as_str());
+                }
+            }
+            for (member, inaccessible) in &d.inaccessible_members {
+                let kind = if member.contains('(') {
+                    "Method"
+                } else {
+                    "Field"
+                };
+                result.push_str(
+                    format!(
+                        "\t{kind} {member} is {}, consumers: {}\n",
+                        inaccessible.access,
+                        inaccessible.consumers.len()
+                    )
+                    .as_str(),
+                );
+                for consumer in &inaccessible.consumers {
+                    result.push_str(format!("\t\t{}\n", format_consumer(consumer)).as_str());
+                }
+            }
+            for (method, consumers) in &d.unimplemented_methods {
+                result.push_str(
+                    format!(
+                        "\tMethod {method} is not implemented, consumers: {}\n",
+                        consumers.len()
+                    )
+                    .as_str(),
+                );
+                for consumer in consumers {
+                    result.push_str(format!("\t\t{}\n", format_consumer(consumer)).as_str());
+                }
+            }
+        }
+        if !self.missing_packages.is_empty() {
+            result.push_str("Missing packages\n");
+            for package in &self.missing_packages {
+                result.push_str(
+                    format!(
+                        "\t{}, classes: {}, consumers: {}, archives: {}\n",
+                        package.name,
+                        package.classes,
+                        package.consumer_count,
+                        package.archive_count
+                    )
+                    .as_str(),
+                );
+            }
+        }
+        result
+    }
+}
+
+impl<'a> Dependents<'a> {
+    fn all_consumers(&self) -> BTreeSet<Consumer<'a>> {
+        let mismatches = self.static_mismatches.values().map(|m| &m.consumers);
+        let inaccessible = self.inaccessible_members.values().map(|m| &m.consumers);
+        let members = self.methods.values().chain(self.fields.values());
+        let unimplemented = self.unimplemented_methods.values();
+        let members = members
+            .chain(mismatches)
+            .chain(inaccessible)
+            .chain(unimplemented);
+        self.consumers
+            .iter()
+            .chain(members.flatten())
+            .copied()
+            .collect()
+    }
+}
+
+fn archives<'a>(consumers: &BTreeSet<Consumer<'a>>) -> BTreeSet<&'a Path> {
+    consumers.iter().filter_map(|c| c.archive).collect()
+}
+
+fn format_consumer(consumer: &Consumer) -> String {
+    match consumer.archive {
+        Some(archive) => format!("{} ({})", consumer.name, archive.display()),
+        None => consumer.name.to_owned(),
+    }
+}
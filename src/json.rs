@@ -0,0 +1,170 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use api_checker::ApiDifference;
+use java_class::{ClassOrigin, DuplicateClass, MissingReference};
+use reference_checker::ClassRequirements;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Checked, baseline::Diff, inverted::Inverted, suppression::Applied};
+
+/// Increased on every incompatible change of the JSON output.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize)]
+pub struct Metadata<'a> {
+    tool: &'static str,
+    version: &'static str,
+    classpath: &'a str,
+    //the class information file, or the embedded Java version
+    jdk: Option<String>,
+    //seconds since the Unix epoch
+    timestamp: u64,
+}
+
+impl<'a> Metadata<'a> {
+    pub fn new(classpath: &'a str, jdk: Option<String>) -> Self {
+        Metadata {
+            tool: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+            classpath,
+            jdk,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    metadata: Metadata<'a>,
+    reports: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct ApiDiffDocument<'a> {
+    schema_version: u32,
+    metadata: Metadata<'a>,
+    old_classpath: &'a str,
+    api_differences: &'a [ApiDifference],
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    release: Option<u16>,
+    results: Vec<ConsumerResult<'a>>,
+    duplicates: Vec<Duplicate<'a>>,
+    missing_references: &'a [MissingReference],
+    failures: Vec<Failure<'a>>,
+    //only with a suppression file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suppression: Option<&'a Applied<'a>>,
+    //only with a baseline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline: Option<&'a Diff<'a>>,
+    //the results keyed by the required classes, only if requested
+    #[serde(flatten)]
+    inverted: Option<Inverted<'a>>,
+}
+
+#[derive(Serialize)]
+struct ConsumerResult<'a> {
+    #[serde(flatten)]
+    requirements: &'a ClassRequirements<'a>,
+    origin: Option<&'a ClassOrigin>,
+}
+
+#[derive(Serialize)]
+struct Duplicate<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    duplicate: &'a DuplicateClass,
+}
+
+#[derive(Serialize)]
+struct Failure<'a> {
+    path: &'a Path,
+    entry: Option<&'a str>,
+    error: String,
+}
+
+/// Converts the result of one check to the JSON value of a report.
+pub fn report(checked: &Checked) -> Value {
+    to_value(checked, None)
+}
+
+/// Like [`report`], with the results keyed by the required classes as well.
+pub fn inverted_report(checked: &Checked) -> Value {
+    let inverted = Inverted::new(&checked.requirements, &checked.classpath.origins);
+    to_value(checked, Some(inverted))
+}
+
+fn to_value(checked: &Checked, inverted: Option<Inverted>) -> Value {
+    let classpath = checked.classpath;
+    let report = Report {
+        release: checked.release,
+        results: checked
+            .requirements
+            .iter()
+            .map(|requirements| ConsumerResult {
+                requirements,
+                origin: classpath.origins.get(requirements.name()),
+            })
+            .collect(),
+        duplicates: classpath
+            .duplicates
+            .iter()
+            .map(|(name, duplicate)| Duplicate { name, duplicate })
+            .collect(),
+        missing_references: &classpath.missing_references,
+        failures: classpath
+            .failures
+            .iter()
+            .map(|failure| Failure {
+                path: &failure.path,
+                entry: failure.entry.as_deref(),
+                error: failure.cause.to_string(),
+            })
+            .collect(),
+        suppression: checked.suppression.as_ref(),
+        baseline: checked.baseline.as_ref(),
+        inverted,
+    };
+    serde_json::to_value(report).expect("Failed to serialize report")
+}
+
+pub fn format(metadata: Metadata, reports: impl IntoIterator<Item = Value>) -> String {
+    let document = Document {
+        schema_version: SCHEMA_VERSION,
+        metadata,
+        reports: reports.into_iter().collect(),
+    };
+    serde_json::to_string_pretty(&document).expect("Failed to serialize report")
+}
+
+pub fn format_api_diff(
+    metadata: Metadata,
+    old_classpath: &str,
+    differences: &[ApiDifference],
+) -> String {
+    let document = ApiDiffDocument {
+        schema_version: SCHEMA_VERSION,
+        metadata,
+        old_classpath,
+        api_differences: differences,
+    };
+    serde_json::to_string_pretty(&document).expect("Failed to serialize report")
+}
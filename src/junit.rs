@@ -0,0 +1,162 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{collections::BTreeMap, path::Path};
+
+use reference_checker::ClassRequirements;
+
+use crate::{Checked, HashMap};
+
+/// A JAR or class directory, with one test case per class.
+#[derive(Debug)]
+pub struct TestSuite {
+    name: String,
+    cases: Vec<TestCase>,
+}
+
+#[derive(Debug)]
+struct TestCase {
+    name: String,
+    outcome: Outcome,
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Passed,
+    //the class has unmet dependencies
+    Failure(String),
+    //the class or archive could not be read
+    Error(String),
+}
+
+/// Converts the result of one check to test suites, one per archive.
+pub fn report(checked: &Checked) -> Vec<TestSuite> {
+    let classpath = checked.classpath;
+    let requirements: HashMap<&str, &ClassRequirements> =
+        checked.requirements.iter().map(|r| (r.name(), r)).collect();
+    let provider = |class: &str| {
+        classpath
+            .origins
+            .get(class)
+            .map(|origin| origin.archive.display().to_string())
+    };
+
+    let mut suites: BTreeMap<&Path, BTreeMap<String, Outcome>> = BTreeMap::new();
+    for (name, origin) in &classpath.origins {
+        let outcome = match requirements.get(name.as_str()) {
+            Some(r) => Outcome::Failure(r.format_with_providers(provider)),
+            None => Outcome::Passed,
+        };
+        suites
+            .entry(&origin.archive)
+            .or_default()
+            .insert(name.clone(), outcome);
+    }
+    for failure in &classpath.failures {
+        let name = failure
+            .entry
+            .clone()
+            .unwrap_or_else(|| failure.path.display().to_string());
+        suites
+            .entry(&failure.path)
+            .or_default()
+            .insert(name, Outcome::Error(failure.cause.to_string()));
+    }
+
+    suites
+        .into_iter()
+        .map(|(archive, cases)| TestSuite {
+            name: match checked.release {
+                Some(release) => format!("{} (release {release})", archive.display()),
+                None => archive.display().to_string(),
+            },
+            cases: cases
+                .into_iter()
+                .map(|(name, outcome)| TestCase { name, outcome })
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn format(suites: impl IntoIterator<Item = TestSuite>) -> String {
+    let suites: Vec<TestSuite> = suites.into_iter().collect();
+    let count = |suite: &TestSuite, f: fn(&Outcome) -> bool| {
+        suite.cases.iter().filter(|c| f(&c.outcome)).count()
+    };
+    let failures = |suite: &TestSuite| count(suite, |o| matches!(o, Outcome::Failure(_)));
+    let errors = |suite: &TestSuite| count(suite, |o| matches!(o, Outcome::Error(_)));
+
+    let mut result = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n".to_owned();
+    result.push_str(
+        format!(
+            "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+            env!("CARGO_PKG_NAME"),
+            suites.iter().map(|s| s.cases.len()).sum::<usize>(),
+            suites.iter().map(failures).sum::<usize>(),
+            suites.iter().map(errors).sum::<usize>(),
+        )
+        .as_str(),
+    );
+    for suite in &suites {
+        result.push_str(
+            format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+                escape(&suite.name),
+                suite.cases.len(),
+                failures(suite),
+                errors(suite),
+            )
+            .as_str(),
+        );
+        for case in &suite.cases {
+            let attributes = format!(
+                "name=\"{}\" classname=\"{}\"",
+                escape(&case.name),
+                escape(&suite.name)
+            );
+            let (element, message) = match &case.outcome {
+                Outcome::Passed => {
+                    result.push_str(format!("    <testcase {attributes}/>\n").as_str());
+                    continue;
+                }
+                Outcome::Failure(message) => ("failure", message),
+                Outcome::Error(message) => ("error", message),
+            };
+            let message = escape(message);
+            result.push_str(format!("    <testcase {attributes}>\n").as_str());
+            result.push_str(
+                format!("      <{element} message=\"{message}\">{message}</{element}>\n").as_str(),
+            );
+            result.push_str("    </testcase>\n");
+        }
+        result.push_str("  </testsuite>\n");
+    }
+    result.push_str("</testsuites>");
+    result
+}
+
+/// Escapes text for use in XML attributes and elements. Line breaks and tabs are kept as
+/// character references, as attribute values are normalized otherwise.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            '\n' => result.push_str("&#10;"),
+            '\t' => result.push_str("&#9;"),
+            //not allowed in XML 1.0 at all
+            c if c < ' ' => result.push('\u{FFFD}'),
+            c => result.push(c),
+        }
+    }
+    result
+}
@@ -7,27 +7,72 @@
 */
 
 mod args;
+mod baseline;
 mod error;
-use std::{fs::File, io::Write};
+mod inverted;
+mod json;
+mod junit;
+mod sarif;
+mod suppression;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Write,
+    path::Path,
+    process::ExitCode,
+};
 
 use ahash::AHashMap;
-use args::Args;
+use args::{Args, FindingCategory, OutputFormat};
 use clap::Parser;
 use env_logger::Env;
 use java_class::{
+    ClassOrigin, Classpath, DuplicateClass, MissingReference, ParseOptions,
     classinfo::{self, ClassInfo},
+    error::{ArchiveError, FormatError},
+    expand_classpath,
+    java_class::Class,
     parse_classpath,
 };
-use log::{debug, info, trace};
-use reference_checker::{ClassRequirements, check_classes};
+use log::{debug, info, trace, warn};
+use reference_checker::{ClassRequirements, DependencyStatus, check_classes};
+use signature_checker::{SignatureReport, verify_jars};
 
-use crate::error::ArgError;
+use crate::{
+    baseline::{Baseline, Diff},
+    error::ArgError,
+    inverted::Inverted,
+    suppression::{Applied, Suppressions},
+};
 
 type HashMap<K, V> = AHashMap<K, V>;
 
-fn main() -> Result<(), error::Error> {
+/// Exit code if there are more findings than `--max-findings`.
+const EXIT_FINDINGS: u8 = 1;
+/// Exit code if the check itself failed.
+const EXIT_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
     env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
     let args = Args::parse();
+    match run(&args) {
+        Ok(findings) if findings > args.max_findings => {
+            info!(
+                "{findings} findings, more than the maximum of {}",
+                args.max_findings
+            );
+            ExitCode::from(EXIT_FINDINGS)
+        }
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e:?}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+/// Runs the check and returns the number of findings.
+fn run(args: &Args) -> Result<usize, error::Error> {
     #[cfg(not(without_version))]
     info!(
         "Version {}",
@@ -44,6 +89,29 @@ fn main() -> Result<(), error::Error> {
             .build_global()?;
     }
 
+    if args.verify_signatures {
+        if args.format != OutputFormat::Text {
+            return Err(error::Error::from(ArgError::IllegalCombination(
+                "Signature verification only supports the text format".to_owned(),
+            )));
+        }
+        info!("Starting signature verification...");
+        let jars = expand_classpath(&args.classpath)?;
+        let reports = verify_jars(&jars, parallel)?;
+        info!("Done.");
+        output(args.output_file.as_deref(), &format_signatures(&reports))?;
+        return Ok(count_signature_findings(&reports));
+    }
+
+    if let Some(old_classpath) = &args.api_diff {
+        return api_diff(args, old_classpath, parallel);
+    }
+    if args.inverted && !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(error::Error::from(ArgError::IllegalCombination(
+            "The inverted report only supports the text and JSON formats".to_owned(),
+        )));
+    }
+
     #[cfg(feature = "embedded_classinfo")]
     let embedded_classinfo: HashMap<u16, &'static str> = {
         let mut map = HashMap::default();
@@ -54,23 +122,26 @@ fn main() -> Result<(), error::Error> {
         map
     };
     #[cfg(feature = "embedded_classinfo")]
-    let classinfo_data = match args.jdk_classinfo {
+    let classinfo_data = match &args.jdk_classinfo {
         Some(path) => {
             info!("Reading ClassInfo from {}", path);
             Some(std::fs::read_to_string(path)?)
         }
         None => {
             #[cfg(feature = "embedded_classinfo")]
-            let loaded = load_embedded(&args, &embedded_classinfo);
+            let loaded = load_embedded(args, &embedded_classinfo);
             #[cfg(not(feature = "embedded_classinfo"))]
             let loaded = None;
             loaded
         }
     };
     #[cfg(not(feature = "embedded_classinfo"))]
-    let classinfo_data = {
-        info!("Reading ClassInfo from {}", &args.jdk_classinfo);
-        Some(std::fs::read_to_string(&args.jdk_classinfo)?)
+    let classinfo_data = match &args.jdk_classinfo {
+        Some(path) => {
+            info!("Reading ClassInfo from {}", path);
+            Some(std::fs::read_to_string(path)?)
+        }
+        None => None,
     };
 
     if classinfo_data.is_none() {
@@ -82,19 +153,351 @@ fn main() -> Result<(), error::Error> {
     let java_classes = read_classinfo(classinfo_data.as_ref().unwrap())?;
     trace!("{:?}", java_classes);
 
+    let filters = Filters {
+        suppressions: args
+            .suppressions
+            .as_deref()
+            .map(Suppressions::read)
+            .transpose()?,
+        baseline: args.baseline.as_deref().map(Baseline::read).transpose()?,
+        before: args.before.clone(),
+    };
+    let options = ParseOptions {
+        parallel,
+        keep_going: args.keep_going,
+        release: release(args),
+        manifest_classpath: args.manifest_classpath,
+    };
     info!("Starting processing...");
-    let classes = parse_classpath(&args.classpath, parallel)?;
-    let unmet_deps =
-        check_classes(&classes, parallel, &java_classes).expect("Failed to get result");
+    let (result, findings) = match args.format {
+        OutputFormat::Text => {
+            let render: fn(&Checked) -> String = if args.inverted {
+                format_inverted
+            } else {
+                format_text
+            };
+            let (reports, findings) =
+                check_releases(args, options, &java_classes, &filters, render)?;
+            let result = if args.all_releases {
+                reports
+                    .into_iter()
+                    .map(|(release, report)| match release {
+                        Some(release) => format!("Release {release}:\n{report}"),
+                        None => format!("Release base:\n{report}"),
+                    })
+                    .collect()
+            } else {
+                reports.into_iter().map(|(_, report)| report).collect()
+            };
+            (result, findings)
+        }
+        OutputFormat::Json => {
+            let render: fn(&Checked) -> serde_json::Value = if args.inverted {
+                json::inverted_report
+            } else {
+                json::report
+            };
+            let (reports, findings) =
+                check_releases(args, options, &java_classes, &filters, render)?;
+            let metadata = json::Metadata::new(&args.classpath, jdk(args));
+            let result = json::format(metadata, reports.into_iter().map(|(_, report)| report));
+            (result, findings)
+        }
+        OutputFormat::Sarif => {
+            let (runs, findings) =
+                check_releases(args, options, &java_classes, &filters, sarif::report)?;
+            (
+                sarif::format(runs.into_iter().map(|(_, run)| run)),
+                findings,
+            )
+        }
+        OutputFormat::Junit => {
+            let (suites, findings) =
+                check_releases(args, options, &java_classes, &filters, junit::report)?;
+            let result = junit::format(suites.into_iter().flat_map(|(_, suites)| suites));
+            (result, findings)
+        }
+    };
+    info!("Done.");
+    output(args.output_file.as_deref(), &result)?;
+    Ok(findings)
+}
+
+/// The unmet dependencies of a class path, checked for one release.
+struct Checked<'a> {
+    release: Option<u16>,
+    classpath: &'a Classpath,
+    //sorted by class name
+    requirements: Vec<ClassRequirements<'a>>,
+    //only with a suppression file
+    suppression: Option<Applied<'a>>,
+    //only with a baseline, which leaves just the new findings in `requirements`
+    baseline: Option<Diff<'a>>,
+    //only when comparing with the class path before an upgrade, the baseline then
+    before: Option<&'a Classpath>,
+}
+
+/// Known findings to leave out of the report.
+#[derive(Default)]
+struct Filters {
+    suppressions: Option<Suppressions>,
+    baseline: Option<Baseline>,
+    //the class path before an upgrade, checked with the same options to serve as baseline
+    before: Option<String>,
+}
+
+/// The rendered checks by release, and the highest number of findings of a single check.
+type Reports<T> = (Vec<(Option<u16>, T)>, usize);
+
+/// Checks the class path once, or with `--all-releases` once for the base classes and once for
+/// every release found in multi-release JARs. Each check is rendered by `render`. Also returns
+/// the highest number of findings of a single check, counting the `--fail-on` categories.
+fn check_releases<T>(
+    args: &Args,
+    mut options: ParseOptions,
+    java_classes: &HashMap<&str, ClassInfo<'_>>,
+    filters: &Filters,
+    render: impl Fn(&Checked) -> T,
+) -> Result<Reports<T>, error::Error> {
+    let render = |checked: &Checked| (render(checked), count_findings(checked, &args.fail_on));
+    if !args.all_releases {
+        let ((report, findings), _) =
+            check_classpath(&args.classpath, &options, java_classes, filters, render)?;
+        return Ok((vec![(options.release, report)], findings));
+    }
+    options.release = None;
+    let ((base, mut findings), releases) =
+        check_classpath(&args.classpath, &options, java_classes, filters, render)?;
+    let mut result = vec![(None, base)];
+    for release in releases {
+        info!("Checking release {release}...");
+        options.release = Some(release);
+        let ((report, release_findings), _) =
+            check_classpath(&args.classpath, &options, java_classes, filters, render)?;
+        result.push((Some(release), report));
+        findings = findings.max(release_findings);
+    }
+    Ok((result, findings))
+}
+
+/// The number of findings in the given categories, or in all categories if none are given.
+/// Every missing method and field counts, but a missing class only once.
+fn count_findings(checked: &Checked, categories: &[FindingCategory]) -> usize {
+    let counted = |category| categories.is_empty() || categories.contains(&category);
+    let count = |category, n: usize| if counted(category) { n } else { 0 };
+    checked
+        .requirements
+        .iter()
+        .flat_map(|r| r.dependencies())
+        .map(|d| match d.status {
+            DependencyStatus::Missing => count(FindingCategory::MissingClass, 1),
+            DependencyStatus::KindMismatch => count(FindingCategory::KindMismatch, 1),
+            DependencyStatus::Inaccessible => count(FindingCategory::IllegalAccess, 1),
+            DependencyStatus::ApiMismatch => {
+                count(FindingCategory::MissingMethod, d.methods.len())
+                    + count(FindingCategory::MissingField, d.fields.len())
+                    + count(FindingCategory::StaticMismatch, d.static_mismatches.len())
+                    + count(FindingCategory::IllegalAccess, d.inaccessible_members.len())
+                    + count(
+                        FindingCategory::UnimplementedMethod,
+                        d.unimplemented_methods.len(),
+                    )
+            }
+        })
+        .sum()
+}
+
+/// Compares the API of the class path with its old version and returns the number of binary
+/// incompatible differences.
+fn api_diff(args: &Args, old_classpath: &str, parallel: bool) -> Result<usize, error::Error> {
+    if !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(error::Error::from(ArgError::IllegalCombination(
+            "The API comparison only supports the text and JSON formats".to_owned(),
+        )));
+    }
+    let options = ParseOptions {
+        parallel,
+        keep_going: args.keep_going,
+        release: release(args),
+        manifest_classpath: args.manifest_classpath,
+    };
+    info!("Starting API comparison with {old_classpath}...");
+    let old = parse_classpath(old_classpath, &options)?;
+    let new = parse_classpath(&args.classpath, &options)?;
+    let differences = api_checker::compare(&old.classes, &new.classes)?;
     info!("Done.");
+    let result = match args.format {
+        OutputFormat::Json => json::format_api_diff(
+            json::Metadata::new(&args.classpath, None),
+            old_classpath,
+            &differences,
+        ),
+        _ => api_checker::format(&differences),
+    };
+    output(args.output_file.as_deref(), &result)?;
+    Ok(differences.iter().filter(|d| d.binary_incompatible).count())
+}
+
+/// The number of problems found while verifying signatures.
+fn count_signature_findings(reports: &[SignatureReport]) -> usize {
+    reports
+        .iter()
+        .map(|r| r.findings.len() + r.signers.iter().filter(|s| s.problem.is_some()).count())
+        .sum()
+}
+
+/// Checks the class path and returns the rendered report together with the releases found in
+/// multi-release JARs.
+fn check_classpath<T>(
+    cp: &str,
+    options: &ParseOptions,
+    java_classes: &HashMap<&str, ClassInfo<'_>>,
+    filters: &Filters,
+    render: impl Fn(&Checked) -> T,
+) -> Result<(T, BTreeSet<u16>), error::Error> {
+    let before = match &filters.before {
+        Some(before) => {
+            info!("Checking the class path before the upgrade...");
+            Some(parse_classpath(before, options)?)
+        }
+        None => None,
+    };
+    let upgrade = match &before {
+        Some(before) => {
+            let (mut requirements, _) =
+                unmet_dependencies(&before.classes, &before.origins, options, java_classes)?;
+            if let Some(suppressions) = &filters.suppressions {
+                suppressions.apply(&mut requirements);
+            }
+            Some(Baseline::from_requirements(options.release, &requirements))
+        }
+        None => None,
+    };
+
+    let mut classpath = parse_classpath(cp, options)?;
+    let (mut requirements, failures) = unmet_dependencies(
+        &classpath.classes,
+        &classpath.origins,
+        options,
+        java_classes,
+    )?;
+    classpath.failures.extend(failures);
+    let suppression = filters
+        .suppressions
+        .as_ref()
+        .map(|s| s.apply(&mut requirements));
+    let baseline = filters
+        .baseline
+        .as_ref()
+        .or(upgrade.as_ref())
+        .map(|b| b.apply(options.release, &mut requirements));
+    let checked = Checked {
+        release: options.release,
+        classpath: &classpath,
+        requirements,
+        suppression,
+        baseline,
+        before: before.as_ref(),
+    };
+    Ok((render(&checked), classpath.releases.clone()))
+}
+
+/// The unmet dependencies of the classes on the class path, sorted by class name, and the
+/// classes that could not be checked. These fail the check unless keeping going.
+fn unmet_dependencies<'a>(
+    classes: &'a HashMap<String, Class>,
+    origins: &HashMap<String, ClassOrigin>,
+    options: &ParseOptions,
+    java_classes: &HashMap<&'a str, ClassInfo<'_>>,
+) -> Result<(Vec<ClassRequirements<'a>>, Vec<ArchiveError>), error::Error> {
+    let (unmet_deps, invalid) = check_classes(classes, options.parallel, java_classes);
+    let mut failures: Vec<ArchiveError> = invalid
+        .into_iter()
+        .map(|invalid| {
+            let origin = origins.get(&invalid.class).cloned().unwrap_or_default();
+            ArchiveError {
+                path: origin.archive,
+                entry: Some(origin.entry),
+                cause: Box::new(FormatError(invalid.cause).into()),
+            }
+        })
+        .collect();
+    if !options.keep_going && !failures.is_empty() {
+        return Err(java_class::error::Error::from(failures.swap_remove(0)).into());
+    }
+    for failure in &failures {
+        warn!("Skipping {failure}");
+    }
+    let mut requirements: Vec<ClassRequirements<'_>> = unmet_deps.into_iter().collect();
+    requirements.sort();
+    Ok((requirements, failures))
+}
+
+fn format_text(checked: &Checked) -> String {
+    let classpath = checked.classpath;
+    let mut result = match checked.before {
+        Some(before) => format_by_replaced(&checked.requirements, before, &classpath.origins),
+        None => format_by_origin(&checked.requirements, &classpath.origins),
+    };
+    result.push_str(format_other_findings(checked).as_str());
+    result
+}
+
+/// Formats the requirements keyed by the required classes.
+fn format_inverted(checked: &Checked) -> String {
+    let mut result = Inverted::new(&checked.requirements, &checked.classpath.origins).format();
+    result.push_str(format_other_findings(checked).as_str());
+    result
+}
+
+/// Formats everything but the requirements.
+fn format_other_findings(checked: &Checked) -> String {
+    let classpath = checked.classpath;
+    let mut result = String::new();
+    result.push_str(format_duplicates(&classpath.duplicates).as_str());
+    result.push_str(format_missing_references(&classpath.missing_references).as_str());
+    result.push_str(format_failures(&classpath.failures).as_str());
+    if let Some(suppression) = &checked.suppression {
+        result.push_str(format_suppression(suppression).as_str());
+    }
+    if let Some(diff) = &checked.baseline {
+        result.push_str(format_diff(diff).as_str());
+    }
+    result
+}
+
+/// A description of the JDK class information in use.
+#[cfg(feature = "embedded_classinfo")]
+fn jdk(args: &Args) -> Option<String> {
+    match (&args.jdk_classinfo, args.java_version) {
+        (Some(path), _) => Some(path.clone()),
+        (None, Some(version)) => Some(format!("embedded:{}", version.numerical())),
+        (None, None) => None,
+    }
+}
 
-    let mut sorted: Vec<ClassRequirements<'_>> = Vec::with_capacity(unmet_deps.capacity());
-    sorted.extend(unmet_deps);
-    sorted.sort();
-    if let Some(path) = args.output_file {
-        write_output(&path, &format(sorted))?;
+/// A description of the JDK class information in use.
+#[cfg(not(feature = "embedded_classinfo"))]
+fn jdk(args: &Args) -> Option<String> {
+    args.jdk_classinfo.clone()
+}
+
+#[cfg(feature = "embedded_classinfo")]
+fn release(args: &Args) -> Option<u16> {
+    args.release
+        .or_else(|| args.java_version.map(|v| v.numerical()))
+}
+
+#[cfg(not(feature = "embedded_classinfo"))]
+fn release(args: &Args) -> Option<u16> {
+    args.release
+}
+
+fn output(path: Option<&str>, content: &str) -> Result<(), error::Error> {
+    if let Some(path) = path {
+        write_output(path, content)?;
     } else {
-        println!("{}", format(sorted));
+        println!("{}", content);
     }
     Ok(())
 }
@@ -110,10 +513,161 @@ fn write_output(path: &str, content: &str) -> Result<(), error::Error> {
     Ok(())
 }
 
-fn format(dep: Vec<ClassRequirements>) -> String {
-    let mut result = String::with_capacity(dep.capacity());
-    for d in dep {
-        result.push_str(d.format().as_str());
+/// Formats the requirements grouped by the archive containing the consuming classes.
+fn format_by_origin(
+    requirements: &[ClassRequirements<'_>],
+    origins: &HashMap<String, ClassOrigin>,
+) -> String {
+    let mut grouped: BTreeMap<&Path, Vec<&ClassRequirements<'_>>> = BTreeMap::new();
+    for r in requirements {
+        grouped
+            .entry(origin_archive(r, origins))
+            .or_default()
+            .push(r);
+    }
+    let mut result = String::new();
+    for (archive, requirements) in grouped {
+        result.push_str(format!("From {}\n", archive.display()).as_str());
+        for d in requirements {
+            result.push_str(d.format_with_providers(provider(origins)).as_str());
+        }
+    }
+    result
+}
+
+/// Formats the requirements grouped by the archive that provided the required classes before
+/// the upgrade.
+fn format_by_replaced(
+    requirements: &[ClassRequirements<'_>],
+    before: &Classpath,
+    origins: &HashMap<String, ClassOrigin>,
+) -> String {
+    let mut grouped: BTreeMap<Option<&Path>, Vec<ClassRequirements<'_>>> = BTreeMap::new();
+    for r in requirements {
+        let parts = r.partition_by(|class| {
+            before
+                .origins
+                .get(class)
+                .map(|origin| origin.archive.as_path())
+        });
+        for (archive, part) in parts {
+            grouped.entry(archive).or_default().push(part);
+        }
+    }
+    let mut result = String::new();
+    for (archive, requirements) in grouped {
+        match archive {
+            Some(archive) => {
+                result.push_str(format!("Previously provided by {}\n", archive.display()).as_str())
+            }
+            None => result.push_str("Not previously provided\n"),
+        }
+        for d in requirements {
+            result.push_str(d.format_with_providers(provider(origins)).as_str());
+        }
+    }
+    result
+}
+
+/// Names the archive providing a class.
+fn provider(origins: &HashMap<String, ClassOrigin>) -> impl Fn(&str) -> Option<String> + '_ {
+    |class: &str| {
+        origins
+            .get(class)
+            .map(|origin| origin.archive.display().to_string())
+    }
+}
+
+/// The archive containing the consuming class, empty if unknown.
+fn origin_archive<'a>(
+    requirements: &ClassRequirements<'_>,
+    origins: &'a HashMap<String, ClassOrigin>,
+) -> &'a Path {
+    origins
+        .get(requirements.name())
+        .map_or(Path::new(""), |origin| origin.archive.as_path())
+}
+
+fn format_failures(failures: &[ArchiveError]) -> String {
+    if failures.is_empty() {
+        return String::new();
+    }
+    let mut result = "Failed to read\n".to_owned();
+    for failure in failures {
+        result.push('\t');
+        result.push_str(failure.to_string().as_str());
+        result.push('\n');
+    }
+    result
+}
+
+fn format_duplicates(duplicates: &BTreeMap<String, DuplicateClass>) -> String {
+    if duplicates.is_empty() {
+        return String::new();
+    }
+    let mut result = "Duplicate classes\n".to_owned();
+    for (name, duplicate) in duplicates {
+        let difference = if duplicate.methods_differ {
+            "methods differ"
+        } else if duplicate.bytes_differ {
+            "bytes differ"
+        } else {
+            "identical"
+        };
+        result.push_str(format!("\t{name} ({difference})\n").as_str());
+        for archive in &duplicate.archives {
+            result.push_str(format!("\t\t{}\n", archive.display()).as_str());
+        }
+    }
+    result
+}
+
+fn format_missing_references(missing: &[MissingReference]) -> String {
+    if missing.is_empty() {
+        return String::new();
+    }
+    let mut result = "Missing Class-Path entries\n".to_owned();
+    for reference in missing {
+        result.push('\t');
+        result.push_str(reference.to_string().as_str());
+        result.push('\n');
+    }
+    result
+}
+
+fn format_suppression(suppression: &Applied) -> String {
+    let mut result = format!("Suppressed findings: {}\n", suppression.suppressed);
+    for (title, entries) in [
+        ("Unused suppressions", &suppression.unused),
+        ("Expired suppressions", &suppression.expired),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        result.push_str(title);
+        result.push('\n');
+        for entry in entries {
+            result.push_str(format!("\t{entry}\n").as_str());
+        }
+    }
+    result
+}
+
+fn format_diff(diff: &Diff) -> String {
+    let mut result = format!("Known findings: {}\n", diff.known);
+    if !diff.resolved.is_empty() {
+        result.push_str("Resolved findings\n");
+        for finding in &diff.resolved {
+            result.push_str(format!("\t{finding}\n").as_str());
+        }
+    }
+    result
+}
+
+fn format_signatures(reports: &[SignatureReport]) -> String {
+    let mut result = String::new();
+    for report in reports {
+        result.push_str(report.format().as_str());
     }
     result
 }
@@ -127,9 +681,33 @@ fn read_classinfo(data: &str) -> Result<HashMap<&str, ClassInfo<'_>>, error::Err
         result.insert(class_info.name, class_info);
     }
     debug!("ClassInfo: {} JDK classes loaded", result.len());
+    let unchecked = unchecked_jdk_findings(&result);
+    if !unchecked.is_empty() {
+        warn!(
+            "The JDK class information was created by an older jdk_class_reader, {} of JDK classes are not reported",
+            unchecked.join(" and ")
+        );
+    }
     Ok(result)
 }
 
+/// The findings that cannot be found for JDK classes, as the class information lacks what they
+/// need.
+fn unchecked_jdk_findings(java_classes: &HashMap<&str, ClassInfo>) -> Vec<&'static str> {
+    let mut result = vec![];
+    if java_classes.values().all(|c| c.fields.is_none()) {
+        result.push("missing fields");
+    }
+    let methods = || java_classes.values().flat_map(|c| c.methods.values());
+    if methods().all(|m| m.is_static.is_none()) {
+        result.push("static mismatches");
+    }
+    if methods().all(|m| m.is_abstract.is_none()) {
+        result.push("unimplemented methods");
+    }
+    result
+}
+
 #[cfg(feature = "embedded_classinfo")]
 fn load_embedded(args: &Args, embedded: &HashMap<u16, &'static str>) -> Option<String> {
     args.java_version.map(|v| -> String {
@@ -144,6 +722,8 @@ fn load_embedded(args: &Args, embedded: &HashMap<u16, &'static str>) -> Option<S
 
 #[cfg(test)]
 mod test {
+    use java_class::code::{self, Code, Instruction, Operand};
+
     use super::*;
 
     #[test]
@@ -166,6 +746,681 @@ mod test {
         execute_and_compare(25);
     }
 
+    #[test]
+    fn jdk_class_information() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        //21 and 25 still need to be created with the current jdk_class_reader
+        for (release, unchecked) in [
+            (11, vec![]),
+            (17, vec![]),
+            (
+                21,
+                vec![
+                    "missing fields",
+                    "static mismatches",
+                    "unimplemented methods",
+                ],
+            ),
+            (
+                25,
+                vec![
+                    "missing fields",
+                    "static mismatches",
+                    "unimplemented methods",
+                ],
+            ),
+        ] {
+            let classinfo = load_classinfo(pkg_path, release);
+            let java_classes = read_classinfo(&classinfo).unwrap();
+            assert_eq!(
+                unchecked_jdk_findings(&java_classes),
+                unchecked,
+                "release {release}"
+            );
+        }
+    }
+
+    #[test]
+    fn signatures() {
+        use signature_checker::{EntryFinding, JarStatus, verify_jar};
+
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        for (jar, status, findings) in [
+            ("test_jar.jar", JarStatus::Unsigned, 0),
+            ("signed_test_jar.jar", JarStatus::Signed, 0),
+            ("signed_ec_test_jar.jar", JarStatus::Signed, 0),
+            (
+                "partially_signed_test_jar.jar",
+                JarStatus::PartiallySigned,
+                1,
+            ),
+            ("tampered_test_jar.jar", JarStatus::Tampered, 1),
+            ("unsafe_names_test_jar.jar", JarStatus::PartiallySigned, 2),
+        ] {
+            let path = std::path::PathBuf::from(format!("{pkg_path}/testdata/{jar}"));
+            let report = verify_jar(&path).unwrap();
+            assert_eq!(report.status(), status, "{jar}");
+            assert_eq!(report.findings.len(), findings, "{jar}");
+        }
+
+        //entries added after signing under names outside of the archive are not signed either
+        let path = format!("{pkg_path}/testdata/unsafe_names_test_jar.jar");
+        let report = verify_jar(std::path::Path::new(&path)).unwrap();
+        assert_eq!(
+            report.findings,
+            [
+                EntryFinding::Unsigned(
+                    "../org/example/LibraryWithoutDependencies.class".to_owned()
+                ),
+                EntryFinding::Unsigned("/org/example/Injected.class".to_owned()),
+            ]
+        );
+        assert_eq!(report.signed_entries, 2);
+    }
+
+    #[test]
+    fn fields() {
+        use java_class::java_class::ConstPoolEntry;
+
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classpath = parse_classpath(
+            format!("{pkg_path}/testdata/test_jar.jar").as_str(),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let class = &classpath.classes["org/example/LibraryWithDependencies"];
+
+        let mut fields: Vec<String> = class.get_fields().unwrap().into_iter().collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            [
+                "RAND:Lorg/apache/commons/math3/random/UniformRandomGenerator;",
+                "distribution:Lorg/apache/commons/math3/distribution/RealDistribution;",
+                "prefix:Ljava/lang/String;",
+            ]
+        );
+        for field in &class.fields {
+            let name = class.get_utf8(&field.name_index).unwrap();
+            assert_eq!(field.is_public(), name == "RAND", "{name}");
+            assert_eq!(field.is_private(), name != "RAND", "{name}");
+            assert_eq!(field.is_static(), name != "prefix", "{name}");
+            assert!(field.is_final(), "{name}");
+            assert!(class.get_constant_value(field).unwrap().is_none(), "{name}");
+        }
+
+        let class = &classpath.classes["org/example/LibraryWithFieldDependencies"];
+        let constant = |name: &str| {
+            let field = class
+                .fields
+                .iter()
+                .find(|f| class.get_utf8(&f.name_index).unwrap() == name)
+                .unwrap();
+            class.get_constant_value(field).unwrap().unwrap()
+        };
+        assert!(matches!(
+            constant("SCALE"),
+            ConstPoolEntry::Int { value: 1000 }
+        ));
+        let ConstPoolEntry::String { index } = constant("UNIT") else {
+            panic!("UNIT is not a String constant");
+        };
+        assert_eq!(class.get_utf8(index).unwrap(), "ulp");
+    }
+
+    #[test]
+    fn constant_pool() {
+        use java_class::java_class::{Class, ConstPoolEntry};
+        use std::io::Cursor;
+
+        let utf8 = |s: &str| [&[1u8, 0, s.len() as u8], s.as_bytes()].concat();
+        let mut pool = vec![];
+        pool.extend(utf8("Condy")); //#1
+        pool.extend([7, 0, 1]); //#2 Class
+        pool.extend(utf8("java/lang/Object")); //#3
+        pool.extend([7, 0, 3]); //#4 Class
+        pool.extend(utf8("value")); //#5
+        pool.extend(utf8("I")); //#6
+        pool.extend([12, 0, 5, 0, 6]); //#7 NameAndType
+        pool.extend([17, 0, 0, 0, 7]); //#8 Dynamic
+        pool.extend([5, 0, 0, 0, 0, 0, 0, 0, 42]); //#9 and #10 Long
+        pool.extend([0x10, 0, 6]); //#11 MethodType
+        let class_bytes = |pool: &[u8], count: u8| {
+            [
+                &[0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 61, 0, count][..],
+                pool,
+                &[0, 0x21, 0, 2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0],
+            ]
+            .concat()
+        };
+
+        let class = Class::from(&mut Cursor::new(class_bytes(&pool, 12))).unwrap();
+        assert_eq!(class.get_name().unwrap(), "Condy");
+        assert!(matches!(
+            class.const_pool[&8],
+            ConstPoolEntry::Dynamic {
+                bootstrap_index: 0,
+                name_type_index: 7
+            }
+        ));
+        assert!(matches!(
+            class.const_pool[&9],
+            ConstPoolEntry::Long { value: 42 }
+        ));
+        assert!(matches!(
+            class.const_pool[&11],
+            ConstPoolEntry::MethodType {
+                descriptor_index: 6
+            }
+        ));
+
+        pool.extend([2, 0, 0]); //#12 unknown tag
+        let err = Class::from(&mut Cursor::new(class_bytes(&pool, 13))).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unknown constant pool tag 2 at index 12"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn keep_going() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let cp = format!(
+            "{pkg_path}/testdata/test_jar.jar;{pkg_path}/testdata/corrupt_test_jar.jar;{pkg_path}/testdata/requirements_17.txt"
+        );
+
+        let Err(java_class::error::Error::ArchiveError(err)) =
+            parse_classpath(cp.as_str(), &ParseOptions::default())
+        else {
+            panic!("Reading a corrupt class must fail");
+        };
+        assert!(err.path.ends_with("corrupt_test_jar.jar"));
+        assert_eq!(
+            err.entry.as_deref(),
+            Some("org/example/LibraryWithoutDependencies.class")
+        );
+
+        let options = ParseOptions {
+            parallel: true,
+            keep_going: true,
+            ..Default::default()
+        };
+        let classpath = parse_classpath(cp.as_str(), &options).unwrap();
+        assert_eq!(classpath.classes.len(), 3);
+        assert_eq!(classpath.failures.len(), 2);
+        assert!(classpath.failures[0].path.ends_with("corrupt_test_jar.jar"));
+        assert!(classpath.failures[1].path.ends_with("requirements_17.txt"));
+        assert_eq!(classpath.failures[1].entry, None);
+    }
+
+    #[test]
+    fn keep_going_invalid_constant_pool() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        //a method reference of LibraryWithoutDependencies names a missing constant pool entry
+        let cp = format!("{pkg_path}/testdata/invalid_pool_test_jar.jar");
+
+        let Err(error::Error::Parsing(java_class::error::Error::ArchiveError(err))) =
+            check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+        else {
+            panic!("Checking a class with an invalid constant pool index must fail");
+        };
+        assert!(err.path.ends_with("invalid_pool_test_jar.jar"));
+        assert_eq!(
+            err.entry.as_deref(),
+            Some("org/example/LibraryWithoutDependencies.class")
+        );
+
+        for parallel in [false, true] {
+            let options = ParseOptions {
+                parallel,
+                keep_going: true,
+                ..Default::default()
+            };
+            let (text, _) = check_classpath(
+                &cp,
+                &options,
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+            .unwrap();
+            assert!(
+                text.contains("org/example/LibraryWithDependencies\n"),
+                "{text}"
+            );
+            assert!(
+                text.ends_with(
+                    format!(
+                        "Failed to read\n\t{cp}!/org/example/LibraryWithoutDependencies.class: \
+                         FormatError: Not a class info entry at idx 32767!\n"
+                    )
+                    .as_str()
+                ),
+                "{text}"
+            );
+        }
+    }
+
+    const MULTI_RELEASE_REQUIREMENTS: &str = "org/example/MultiReleaseLibrary\n\tClass org/apache/commons/math3/util/FastMath\n\t\tMethod sqrt(D)D\n";
+
+    #[test]
+    fn multi_release() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!("{pkg_path}/testdata/multi_release_test_jar.jar");
+
+        let mut options = ParseOptions::default();
+        let (base, releases) = check_classpath(
+            &cp,
+            &options,
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        assert_eq!(releases.into_iter().collect::<Vec<_>>(), [11]);
+        assert_eq!(base, format!("From {cp}\n{MULTI_RELEASE_REQUIREMENTS}"));
+        for (release, expected) in [(8, base.as_str()), (11, ""), (17, "")] {
+            options.release = Some(release);
+            let (checked, _) = check_classpath(
+                &cp,
+                &options,
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+            .unwrap();
+            assert_eq!(checked, expected, "release {release}");
+        }
+    }
+
+    #[test]
+    fn nested_archives() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let reference =
+            std::fs::read_to_string(format!("{pkg_path}/testdata/requirements_17.txt")).unwrap();
+        for (archive, classes, library, duplicate_count) in [
+            (
+                "boot_test_jar.jar",
+                "boot_test_jar.jar",
+                "boot_test_jar.jar!/BOOT-INF/lib",
+                0,
+            ),
+            //the EAR contains the classes in its web module and library
+            (
+                "test_ear.ear",
+                "test_ear.ear!/web.war",
+                "test_ear.ear!/web.war!/WEB-INF/lib",
+                3,
+            ),
+        ] {
+            let cp = format!("{pkg_path}/testdata/{archive}");
+            let classpath = parse_classpath(&cp, &ParseOptions::default()).unwrap();
+            assert_eq!(classpath.classes.len(), 4, "{archive}");
+            assert_eq!(classpath.duplicates.len(), duplicate_count, "{archive}");
+            let classes = format!("{pkg_path}/testdata/{classes}");
+            let library = format!("{pkg_path}/testdata/{library}/multi_release_test_jar.jar");
+            let origin = &classpath.origins["org/example/LibraryWithDependencies"];
+            assert_eq!(origin.archive, std::path::PathBuf::from(&classes));
+            assert!(
+                origin
+                    .entry
+                    .ends_with("org/example/LibraryWithDependencies.class")
+            );
+            assert_eq!(
+                classpath.origins["org/example/MultiReleaseLibrary"],
+                ClassOrigin {
+                    archive: std::path::PathBuf::from(&library),
+                    entry: "org/example/MultiReleaseLibrary.class".to_owned(),
+                }
+            );
+
+            let (checked, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+            .unwrap();
+            let checked = checked.split("Duplicate classes").next().unwrap();
+            assert_eq!(
+                checked,
+                format!(
+                    "From {classes}\n{}\nFrom {library}\n{MULTI_RELEASE_REQUIREMENTS}",
+                    reference.trim_end()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn class_directories() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let reference =
+            std::fs::read_to_string(format!("{pkg_path}/testdata/requirements_17.txt")).unwrap();
+
+        let cp = format!("{pkg_path}/testdata/classes");
+        let (checked, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        assert_eq!(checked.trim(), format!("From {cp}\n{}", reference.trim()));
+
+        let cp =
+            format!("{pkg_path}/testdata/classes/org/example/LibraryWithoutDependencies.class");
+        let classpath = parse_classpath(&cp, &ParseOptions::default()).unwrap();
+        assert!(
+            classpath
+                .classes
+                .contains_key("org/example/LibraryWithoutDependencies")
+        );
+        assert_eq!(classpath.classes.len(), 1);
+
+        let cp = format!("{pkg_path}/testdata/misplaced_classes");
+        let Err(java_class::error::Error::ArchiveError(err)) =
+            parse_classpath(&cp, &ParseOptions::default())
+        else {
+            panic!("Reading a misplaced class must fail");
+        };
+        assert!(err.path.ends_with("org/LibraryWithoutDependencies.class"));
+        assert!(
+            err.to_string().contains(
+                "Class org/example/LibraryWithoutDependencies does not match its location"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn manifest_classpath() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let cp = format!("{pkg_path}/testdata/manifest_classpath/app.jar");
+
+        let classpath = parse_classpath(&cp, &ParseOptions::default()).unwrap();
+        assert_eq!(classpath.classes.len(), 1);
+        assert!(classpath.missing_references.is_empty());
+
+        let options = ParseOptions {
+            manifest_classpath: true,
+            ..Default::default()
+        };
+        let classpath = parse_classpath(&cp, &options).unwrap();
+        assert_eq!(classpath.classes.len(), 4);
+        assert!(
+            classpath.origins["org/example/LibraryWithDependencies"]
+                .archive
+                .ends_with("manifest_classpath/lib/test_jar.jar")
+        );
+        assert_eq!(
+            classpath.missing_references,
+            [MissingReference {
+                jar: std::path::PathBuf::from(&cp),
+                reference: "missing.jar".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let jar = |name: &str| format!("{pkg_path}/testdata/{name}");
+        let cp = [
+            jar("test_jar.jar"),
+            jar("conflicting_test_jar.jar"),
+            jar("signed_test_jar.jar"),
+        ]
+        .join(";");
+
+        for parallel in [false, true] {
+            let options = ParseOptions {
+                parallel,
+                ..Default::default()
+            };
+            let classpath = parse_classpath(&cp, &options).unwrap();
+            assert_eq!(classpath.duplicates.len(), 3);
+            for (class, methods_differ, bytes_differ, signed) in [
+                ("LibraryWithDependencies", false, false, true),
+                ("LibraryWithFieldDependencies", false, true, false),
+                ("LibraryWithoutDependencies", true, true, true),
+            ] {
+                let name = format!("org/example/{class}");
+                let duplicate = &classpath.duplicates[&name];
+                let mut archives = vec![jar("test_jar.jar"), jar("conflicting_test_jar.jar")];
+                if signed {
+                    archives.push(jar("signed_test_jar.jar"));
+                }
+                assert_eq!(
+                    duplicate.archives,
+                    archives
+                        .into_iter()
+                        .map(std::path::PathBuf::from)
+                        .collect::<Vec<_>>()
+                );
+                assert_eq!(duplicate.methods_differ, methods_differ, "{class}");
+                assert_eq!(duplicate.bytes_differ, bytes_differ, "{class}");
+                assert_eq!(
+                    classpath.origins[&name].archive,
+                    std::path::PathBuf::from(jar("test_jar.jar"))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn duplicates_in_one_archive() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        //the launcher class is also kept in BOOT-INF/classes and the nested library
+        let cp = format!("{pkg_path}/testdata/duplicate_entries_test_jar.jar");
+        for parallel in [false, true] {
+            let options = ParseOptions {
+                parallel,
+                ..Default::default()
+            };
+            let classpath = parse_classpath(&cp, &options).unwrap();
+            let duplicate = &classpath.duplicates["org/example/LibraryWithoutDependencies"];
+            assert_eq!(
+                duplicate.archives,
+                [
+                    cp.clone(),
+                    cp.clone(),
+                    format!("{cp}!/BOOT-INF/lib/test_jar.jar")
+                ]
+                .into_iter()
+                .map(std::path::PathBuf::from)
+                .collect::<Vec<_>>()
+            );
+            assert!(!duplicate.methods_differ);
+            assert!(!duplicate.bytes_differ);
+        }
+    }
+
+    #[test]
+    fn origins() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let consumer = format!("{pkg_path}/testdata/consumer_test_jar.jar");
+        let provider = format!("{pkg_path}/testdata/conflicting_test_jar.jar");
+
+        let (checked, _) = check_classpath(
+            format!("{consumer};{provider}").as_str(),
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        let expected = format!(
+            "From {consumer}\norg/example/Consumer\n\tClass org/example/LibraryWithoutDependencies (API mismatch) (provided by {provider})\n\t\tMethod deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;\n"
+        );
+        assert!(checked.ends_with(&expected), "{checked}");
+
+        let (checked, _) = check_classpath(
+            format!("{consumer};{pkg_path}/testdata/test_jar.jar").as_str(),
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        assert!(!checked.contains("org/example/Consumer"), "{checked}");
+    }
+
+    fn format(dep: Vec<ClassRequirements>) -> String {
+        let mut result = String::with_capacity(dep.capacity());
+        for d in dep {
+            result.push_str(d.format().as_str());
+        }
+        result
+    }
+
+    #[test]
+    fn json_output() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!(
+            "{pkg_path}/testdata/consumer_test_jar.jar;{pkg_path}/testdata/conflicting_test_jar.jar"
+        );
+
+        let (report, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            json::report,
+        )
+        .unwrap();
+        let formatted = json::format(json::Metadata::new(&cp, None), [report]);
+        let document: serde_json::Value = serde_json::from_str(&formatted).unwrap();
+        assert_eq!(document["schema_version"], json::SCHEMA_VERSION);
+        assert_eq!(document["metadata"]["classpath"], cp.as_str());
+
+        let report = &document["reports"][0];
+        assert_eq!(report["release"], serde_json::Value::Null);
+        let results = report["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        let consumer = &results[0];
+        assert_eq!(consumer["name"], "org/example/Consumer");
+        assert_eq!(
+            consumer["origin"]["archive"],
+            format!("{pkg_path}/testdata/consumer_test_jar.jar").as_str()
+        );
+        assert_eq!(consumer["origin"]["entry"], "org/example/Consumer.class");
+        assert_eq!(
+            consumer["dependencies"],
+            serde_json::json!([{
+                "name": "org/example/LibraryWithoutDependencies",
+                "kind": "class",
+                "status": "api_mismatch",
+                "methods": ["deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;"],
+                "fields": [],
+                "static_mismatches": [],
+                "inaccessible_members": [],
+                "unimplemented_methods": [],
+                "usages": ["member_reference"]
+            }])
+        );
+        let field_consumer = results
+            .iter()
+            .find(|r| r["name"] == "org/example/LibraryWithFieldDependencies")
+            .unwrap();
+        let precision = &field_consumer["dependencies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|d| d["name"] == "org/apache/commons/math3/util/Precision")
+            .unwrap();
+        assert_eq!(precision["status"], "missing");
+        assert_eq!(precision["fields"], serde_json::json!(["EPSILON:D"]));
+        assert_eq!(report["duplicates"], serde_json::json!([]));
+        assert_eq!(report["failures"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn sarif_output() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!(
+            "{pkg_path}/testdata/consumer_test_jar.jar;{pkg_path}/testdata/conflicting_test_jar.jar;{pkg_path}/testdata/test_jar.jar"
+        );
+
+        let run = || {
+            let (run, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                sarif::report,
+            )
+            .unwrap();
+            let log: serde_json::Value = serde_json::from_str(&sarif::format([run])).unwrap();
+            log
+        };
+        let log = run();
+        assert_eq!(log["version"], "2.1.0");
+        let run_result = &log["runs"][0];
+        let results = run_result["results"].as_array().unwrap();
+        let rule_ids: BTreeSet<&str> = results
+            .iter()
+            .map(|r| r["ruleId"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            rule_ids,
+            BTreeSet::from(["duplicate-class", "missing-class", "missing-method"])
+        );
+
+        let consumer = results
+            .iter()
+            .find(|r| {
+                r["locations"][0]["logicalLocations"][0]["fullyQualifiedName"]
+                    == "org/example/Consumer"
+            })
+            .unwrap();
+        assert_eq!(consumer["ruleId"], "missing-method");
+        let artifact = &consumer["locations"][0]["physicalLocation"]["artifactLocation"];
+        assert_eq!(artifact["uri"], "org/example/Consumer.class");
+        let artifacts = run_result["artifacts"].as_array().unwrap();
+        let entry = &artifacts[artifact["index"].as_u64().unwrap() as usize];
+        let parent = &artifacts[entry["parentIndex"].as_u64().unwrap() as usize];
+        assert!(
+            parent["location"]["uri"]
+                .as_str()
+                .unwrap()
+                .ends_with("testdata/consumer_test_jar.jar")
+        );
+
+        let fingerprints = |log: &serde_json::Value| -> Vec<serde_json::Value> {
+            log["runs"][0]["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|r| r["partialFingerprints"].clone())
+                .collect()
+        };
+        assert_eq!(fingerprints(&log), fingerprints(&run()));
+    }
+
     fn execute_and_compare(version: u16) {
         let pkg_path = env!("CARGO_MANIFEST_DIR");
 
@@ -174,9 +1429,10 @@ mod test {
 
         let mut jar_path = pkg_path.to_owned();
         jar_path.push_str("/testdata/test_jar.jar");
-        let classes = parse_classpath(jar_path.as_str(), false).unwrap();
+        let classpath = parse_classpath(jar_path.as_str(), &ParseOptions::default()).unwrap();
 
-        let consumed = check_classes(&classes, false, &java_classes).expect("Failed to get result");
+        let (consumed, invalid) = check_classes(&classpath.classes, false, &java_classes);
+        assert!(invalid.is_empty());
 
         let mut sorted: Vec<ClassRequirements<'_>> = Vec::with_capacity(consumed.capacity());
         sorted.extend(consumed);
@@ -189,10 +1445,884 @@ mod test {
         assert_eq!(formatted.trim(), reference.trim());
     }
 
+    #[test]
+    fn junit_output() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!(
+            "{pkg_path}/testdata/consumer_test_jar.jar;{pkg_path}/testdata/conflicting_test_jar.jar;{pkg_path}/testdata/corrupt_test_jar.jar"
+        );
+        let options = ParseOptions {
+            keep_going: true,
+            ..Default::default()
+        };
+
+        let (suites, _) = check_classpath(
+            &cp,
+            &options,
+            &java_classes,
+            &Filters::default(),
+            junit::report,
+        )
+        .unwrap();
+        let xml = junit::format(suites);
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuites name=\"jar_verifier\" tests=\"5\" failures=\"3\" errors=\"1\">"
+        ));
+        assert_eq!(xml.matches("<testsuite ").count(), 3);
+        assert!(xml.contains(&format!(
+            "<testsuite name=\"{pkg_path}/testdata/conflicting_test_jar.jar\" tests=\"3\" failures=\"2\" errors=\"0\">"
+        )));
+        assert!(xml.contains(&format!(
+            "<testcase name=\"org/example/LibraryWithoutDependencies\" classname=\"{pkg_path}/testdata/conflicting_test_jar.jar\"/>"
+        )));
+        assert!(xml.contains(&format!(
+            "<failure message=\"org/example/Consumer&#10;&#9;Class org/example/LibraryWithoutDependencies (API mismatch) (provided by {pkg_path}/testdata/conflicting_test_jar.jar)&#10;&#9;&#9;Method deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;&#10;\">"
+        )));
+        assert!(xml.contains("<error message=\"ParsingError: failed to fill whole buffer\">"));
+        assert!(xml.ends_with("</testsuites>"));
+    }
+
+    #[test]
+    fn finding_thresholds() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!(
+            "{pkg_path}/testdata/consumer_test_jar.jar;{pkg_path}/testdata/conflicting_test_jar.jar"
+        );
+
+        let count = |categories: &[FindingCategory]| {
+            let render = |checked: &Checked| count_findings(checked, categories);
+            check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                render,
+            )
+            .unwrap()
+            .0
+        };
+        let missing_classes = count(&[FindingCategory::MissingClass]);
+        let missing_methods = count(&[FindingCategory::MissingMethod]);
+        //Consumer lacks deepCopy, the commons-math classes are missing entirely
+        assert_eq!(missing_methods, 1);
+        assert_eq!(missing_classes, 5);
+        assert_eq!(count(&[FindingCategory::MissingField]), 0);
+        assert_eq!(count(&[FindingCategory::KindMismatch]), 0);
+        assert_eq!(
+            count(&[
+                FindingCategory::MissingClass,
+                FindingCategory::MissingMethod
+            ]),
+            missing_classes + missing_methods
+        );
+        assert_eq!(count(&[]), missing_classes + missing_methods);
+    }
+
+    #[test]
+    fn suppressions() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!(
+            "{pkg_path}/testdata/consumer_test_jar.jar;{pkg_path}/testdata/conflicting_test_jar.jar"
+        );
+        let suppressions =
+            Suppressions::read(&format!("{pkg_path}/testdata/suppressions.json")).unwrap();
+
+        let render = |checked: &Checked| {
+            let suppression = checked.suppression.as_ref().unwrap();
+            //5 missing commons-math classes and deepCopy, which two entries match
+            assert!(checked.requirements.is_empty());
+            assert_eq!(suppression.suppressed, 6);
+            let unused: Vec<String> = suppression.unused.iter().map(|s| s.to_string()).collect();
+            assert_eq!(
+                unused,
+                [
+                    "consumer org/example/*, dependency org/example/*, member areObjectsEqual(*",
+                    "dependency org/example/Removed*"
+                ]
+            );
+            let expired: Vec<String> = suppression.expired.iter().map(|s| s.to_string()).collect();
+            assert_eq!(expired, ["dependency org/example/**, expires 2020-01-01"]);
+            format_text(checked)
+        };
+        let (text, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters {
+                suppressions: Some(suppressions),
+                ..Default::default()
+            },
+            render,
+        )
+        .unwrap();
+        assert!(
+            text.starts_with("Suppressed findings: 6\nUnused suppressions\n"),
+            "{text}"
+        );
+
+        let (text, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        assert!(!text.contains("Suppressed"), "{text}");
+    }
+
+    #[test]
+    fn baseline() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let consumer = format!("{pkg_path}/testdata/consumer_test_jar.jar");
+        let before = format!("{consumer};{pkg_path}/testdata/conflicting_test_jar.jar");
+        let after = format!("{consumer};{pkg_path}/testdata/test_jar.jar");
+        let options = ParseOptions::default();
+
+        let directory = std::env::temp_dir().join(format!("jar_verifier_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (name, render) in [
+            ("baseline.txt", format_text as fn(&Checked) -> String),
+            ("baseline.json", |checked: &Checked| {
+                json::format(json::Metadata::new("", None), [json::report(checked)])
+            }),
+        ] {
+            let (report, _) = check_classpath(
+                &before,
+                &options,
+                &java_classes,
+                &Filters::default(),
+                render,
+            )
+            .unwrap();
+            let path = directory.join(name);
+            std::fs::write(&path, report).unwrap();
+            let filters = Filters {
+                baseline: Some(Baseline::read(path.to_str().unwrap()).unwrap()),
+                ..Default::default()
+            };
+
+            //deepCopy is provided after the upgrade, the commons-math classes are still missing
+            let render = |checked: &Checked| {
+                let diff = checked.baseline.as_ref().unwrap();
+                assert_eq!(diff.known, 5, "{name}");
+                let resolved: Vec<String> = diff.resolved.iter().map(|f| f.to_string()).collect();
+                assert_eq!(
+                    resolved,
+                    [
+                        "org/example/Consumer: org/example/LibraryWithoutDependencies.deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;"
+                    ],
+                    "{name}"
+                );
+                assert_eq!(count_findings(checked, &[]), 0, "{name}");
+            };
+            check_classpath(&after, &options, &java_classes, &filters, render).unwrap();
+
+            //the class path of the baseline itself has nothing new
+            let render = |checked: &Checked| {
+                let diff = checked.baseline.as_ref().unwrap();
+                assert_eq!(diff.known, 6, "{name}");
+                assert!(diff.resolved.is_empty(), "{name}");
+                assert_eq!(count_findings(checked, &[]), 0, "{name}");
+            };
+            check_classpath(&before, &options, &java_classes, &filters, render).unwrap();
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn baseline_categories() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!("{pkg_path}/testdata/static_mismatch_test_jar.jar");
+        let options = ParseOptions::default();
+
+        let directory =
+            std::env::temp_dir().join(format!("jar_verifier_categories_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        //baselines in which the static mismatch of half(I)I was a missing method
+        let (text, _) = check_classpath(
+            &cp,
+            &options,
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        let text = text.replace(
+            "Method half(I)I is static (usage: invokevirtual)",
+            "Method half(I)I",
+        );
+        let (json, _) = check_classpath(
+            &cp,
+            &options,
+            &java_classes,
+            &Filters::default(),
+            |checked| json::format(json::Metadata::new("", None), [json::report(checked)]),
+        )
+        .unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let utility = &mut json["reports"][0]["results"][0]["dependencies"][1];
+        assert_eq!(utility["name"], "org/example/invoke/Utility");
+        utility["static_mismatches"]
+            .as_array_mut()
+            .unwrap()
+            .retain(|m| m["method"] != "half(I)I");
+        utility["methods"] = serde_json::json!(["half(I)I"]);
+
+        for (name, report) in [("baseline.txt", text), ("baseline.json", json.to_string())] {
+            let path = directory.join(name);
+            std::fs::write(&path, report).unwrap();
+            let filters = Filters {
+                baseline: Some(Baseline::read(path.to_str().unwrap()).unwrap()),
+                ..Default::default()
+            };
+            let render = |checked: &Checked| {
+                let diff = checked.baseline.as_ref().unwrap();
+                assert_eq!(diff.known, 3, "{name}");
+                let resolved: Vec<String> = diff.resolved.iter().map(|f| f.to_string()).collect();
+                assert_eq!(
+                    resolved,
+                    ["org/example/invoke/Caller: org/example/invoke/Utility.half(I)I"],
+                    "{name}"
+                );
+                assert_eq!(
+                    count_findings(checked, &[FindingCategory::StaticMismatch]),
+                    1,
+                    "{name}"
+                );
+                assert_eq!(count_findings(checked, &[]), 1, "{name}");
+            };
+            check_classpath(&cp, &options, &java_classes, &filters, render).unwrap();
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn upgrade_impact() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let consumer = format!("{pkg_path}/testdata/consumer_test_jar.jar");
+        let library = format!("{pkg_path}/testdata/test_jar.jar");
+        let conflicting = format!("{pkg_path}/testdata/conflicting_test_jar.jar");
+        let options = ParseOptions::default();
+
+        let filters = Filters {
+            before: Some(format!("{consumer};{library}")),
+            ..Default::default()
+        };
+        let (text, _) = check_classpath(
+            &format!("{consumer};{conflicting}"),
+            &options,
+            &java_classes,
+            &filters,
+            format_text,
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            format!(
+                "Previously provided by {library}\norg/example/Consumer\n\tClass org/example/LibraryWithoutDependencies (API mismatch) (provided by {conflicting})\n\t\tMethod deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;\nKnown findings: 5\n"
+            )
+        );
+
+        //a library that is new after the upgrade, missing before
+        let filters = Filters {
+            before: Some(consumer.clone()),
+            ..Default::default()
+        };
+        let (text, _) = check_classpath(
+            &format!("{consumer};{conflicting}"),
+            &options,
+            &java_classes,
+            &filters,
+            format_text,
+        )
+        .unwrap();
+        assert!(
+            text.starts_with("Not previously provided\norg/example/Consumer\n"),
+            "{text}"
+        );
+        assert!(
+            text.ends_with("Resolved findings\n\torg/example/Consumer: org/example/LibraryWithoutDependencies\n"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn class_usages() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!("{pkg_path}/testdata/class_usage_test_jar.jar");
+        let (text, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        //Bootstraps.unused() has no call site left, so the method handle of its bootstrap
+        //method is not used
+        assert_eq!(
+            text,
+            format!(
+                "From {cp}
+org/example/usage/Bootstraps
+\tClass org/example/gone/GoneHandle
+\t\tMethod used()V
+org/example/usage/Implementation
+\tInterface org/example/gone/GoneInterface
+org/example/usage/Instructions
+\tClass org/example/gone/GoneArray
+\tClass org/example/gone/GoneCast
+\tClass org/example/gone/GoneCheck
+\tClass org/example/gone/GoneException
+\tClass org/example/gone/GoneLiteral
+\tClass org/example/gone/GoneMatrix
+org/example/usage/Subclass
+\tClass org/example/gone/GoneBase
+\t\tMethod <init>()V
+"
+            )
+        );
+
+        //the kind of usage is only part of the JSON output
+        let (report, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            json::report,
+        )
+        .unwrap();
+        let formatted = json::format(json::Metadata::new(&cp, None), [report]);
+        let document: serde_json::Value = serde_json::from_str(&formatted).unwrap();
+        let usages: Vec<(String, serde_json::Value)> = document["reports"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .flat_map(|result| result["dependencies"].as_array().unwrap())
+            .map(|dep| {
+                (
+                    dep["name"].as_str().unwrap().to_owned(),
+                    dep["usages"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            usages,
+            [
+                ("GoneHandle", serde_json::json!(["member_reference"])),
+                ("GoneInterface", serde_json::json!(["interface"])),
+                ("GoneArray", serde_json::json!(["array_creation"])),
+                ("GoneCast", serde_json::json!(["checkcast"])),
+                ("GoneCheck", serde_json::json!(["instanceof"])),
+                ("GoneException", serde_json::json!(["catch_type"])),
+                ("GoneLiteral", serde_json::json!(["class_literal"])),
+                ("GoneMatrix", serde_json::json!(["array_creation"])),
+                (
+                    "GoneBase",
+                    serde_json::json!(["member_reference", "superclass"])
+                ),
+            ]
+            .map(|(class, usages)| (format!("org/example/gone/{class}"), usages))
+        );
+    }
+
+    #[test]
+    fn static_mismatches() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let cp = format!("{pkg_path}/testdata/static_mismatch_test_jar.jar");
+        //org/example/invoke/Handles uses polymorphic and static methods of the method and var
+        //handles correctly, only describeConstable() is missing before Java 12
+        let handles = "org/example/invoke/Handles
+\tClass java/lang/invoke/MethodHandle (API mismatch)
+\t\tMethod describeConstable()Ljava/util/Optional;
+";
+        let interrupts = "org/example/invoke/Interrupts
+\tClass java/lang/Thread (API mismatch)
+\t\tMethod interrupted()Z is static (usage: invokevirtual)
+";
+        //the class information of 21 and 25 lacks the static flags of JDK methods
+        for (release, handles, interrupts) in [
+            (11, handles, interrupts),
+            (17, "", interrupts),
+            (21, "", ""),
+            (25, "", ""),
+        ] {
+            let classinfo = load_classinfo(pkg_path, release);
+            let java_classes = read_classinfo(&classinfo).unwrap();
+            let (text, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+            .unwrap();
+            assert_eq!(
+                text,
+                format!(
+                    "From {cp}
+org/example/invoke/Caller
+\tInterface org/example/invoke/Greeter (API mismatch) (provided by {cp})
+\t\tMethod greet()Ljava/lang/String; is static (usage: invokeinterface)
+\tClass org/example/invoke/Utility (API mismatch) (provided by {cp})
+\t\tMethod half(I)I is static (usage: invokevirtual)
+\t\tMethod twice(I)I is not static (usage: invokestatic)
+{handles}{interrupts}"
+                ),
+                "release {release}"
+            );
+
+            let (counts, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                |checked| {
+                    (
+                        count_findings(checked, &[FindingCategory::StaticMismatch]),
+                        count_findings(checked, &[FindingCategory::MissingMethod]),
+                    )
+                },
+            )
+            .unwrap();
+            let expected = (
+                3 + usize::from(!interrupts.is_empty()),
+                usize::from(!handles.is_empty()),
+            );
+            assert_eq!(counts, expected, "release {release}");
+        }
+    }
+
+    #[test]
+    fn illegal_access() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let cp = format!("{pkg_path}/testdata/access_test_jar.jar");
+        let (text, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_text,
+        )
+        .unwrap();
+        //the subclass, the class in the same package and the nest mate are fine
+        assert_eq!(
+            text,
+            format!(
+                "From {cp}
+org/example/access/Caller
+\tClass org/example/access/lib/Hidden (inaccessible) (provided by {cp})
+\tClass org/example/access/lib/Library (API mismatch) (provided by {cp})
+\t\tMethod <init>()V is protected
+\t\tMethod hook()V is protected
+\t\tMethod tightened()V is private
+\t\tField counter:I is package-private
+"
+            )
+        );
+
+        let (count, _) = check_classpath(
+            &cp,
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            |checked| count_findings(checked, &[FindingCategory::IllegalAccess]),
+        )
+        .unwrap();
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn unimplemented_methods() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let cp = format!("{pkg_path}/testdata/implementation_test_jar.jar");
+        let runnable = "org/example/implementation/MyTask
+\tInterface java/lang/Runnable (API mismatch)
+\t\tMethod run()V is not implemented
+";
+        //the class information for 21 and 25 doesn't flag abstract methods yet
+        for (release, runnable) in [(11, runnable), (17, runnable), (21, ""), (25, "")] {
+            let classinfo = load_classinfo(pkg_path, release);
+            let java_classes = read_classinfo(&classinfo).unwrap();
+            let (text, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                format_text,
+            )
+            .unwrap();
+            //QuietListener gets onClose from a default method, PartiallyNamed is abstract
+            assert_eq!(
+                text,
+                format!(
+                    "From {cp}
+org/example/implementation/EventListener
+\tInterface org/example/implementation/lib/Listener (API mismatch) (provided by {cp})
+\t\tMethod onClose()V is not implemented
+{runnable}org/example/implementation/Named
+\tClass org/example/implementation/lib/Base (API mismatch) (provided by {cp})
+\t\tMethod size()I is not implemented
+"
+                ),
+                "release {release}"
+            );
+
+            let (count, _) = check_classpath(
+                &cp,
+                &ParseOptions::default(),
+                &java_classes,
+                &Filters::default(),
+                |checked| count_findings(checked, &[FindingCategory::UnimplementedMethod]),
+            )
+            .unwrap();
+            assert_eq!(
+                count,
+                2 + usize::from(!runnable.is_empty()),
+                "release {release}"
+            );
+        }
+    }
+
+    #[test]
+    fn instructions() {
+        use java_class::code::Operand;
+
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classpath = parse_classpath(
+            format!("{pkg_path}/testdata/class_usage_test_jar.jar").as_str(),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let class = &classpath.classes["org/example/usage/Instructions"];
+        let method = class
+            .methods
+            .iter()
+            .find(|m| class.get_utf8(&m.name_index).unwrap() == "jump")
+            .unwrap();
+        let code = method.get_code(class).unwrap().unwrap();
+        let instructions = code.instructions().unwrap();
+
+        let table = instructions
+            .iter()
+            .find(|i| i.mnemonic() == "tableswitch")
+            .unwrap();
+        let Operand::TableSwitch { low, offsets, .. } = &table.operand else {
+            panic!("{table:?}");
+        };
+        assert_eq!((*low, offsets.len()), (0, 3));
+        let lookup = instructions
+            .iter()
+            .find(|i| i.mnemonic() == "lookupswitch")
+            .unwrap();
+        let Operand::LookupSwitch { pairs, .. } = &lookup.operand else {
+            panic!("{lookup:?}");
+        };
+        let keys: Vec<i32> = pairs.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, [-100000, 5, 100000]);
+        //the targets of every branch are instructions
+        for instruction in [table, lookup] {
+            let (Operand::TableSwitch { default, .. } | Operand::LookupSwitch { default, .. }) =
+                instruction.operand
+            else {
+                unreachable!()
+            };
+            let target = instruction.pc as i64 + default as i64;
+            assert!(instructions.iter().any(|i| i.pc as i64 == target));
+        }
+        let wide = instructions.iter().find(|i| i.wide).unwrap();
+        assert_eq!(wide.mnemonic(), "iinc");
+        assert!(matches!(
+            wide.operand,
+            Operand::Increment { value: 1000, .. }
+        ));
+        assert_eq!(instructions.last().unwrap().mnemonic(), "ireturn");
+    }
+
+    #[test]
+    fn inverted_report() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let classinfo = load_classinfo(pkg_path, 17);
+        let java_classes = read_classinfo(&classinfo).unwrap();
+        let consumer = format!("{pkg_path}/testdata/consumer_test_jar.jar");
+        let conflicting = format!("{pkg_path}/testdata/conflicting_test_jar.jar");
+        let (text, _) = check_classpath(
+            &format!("{consumer};{conflicting}"),
+            &ParseOptions::default(),
+            &java_classes,
+            &Filters::default(),
+            format_inverted,
+        )
+        .unwrap();
+        assert!(
+            text.starts_with(&format!(
+                "Class org/apache/commons/math3/distribution/BetaDistribution (missing), consumers: 1, archives: 1\n\torg/example/LibraryWithDependencies ({conflicting})\n"
+            )),
+            "{text}"
+        );
+        assert!(
+            text.contains(&format!(
+                "Class org/example/LibraryWithoutDependencies (API mismatch), consumers: 1, archives: 1\n\tMethod deepCopy([[Ljava/lang/String;)[[Ljava/lang/String;, consumers: 1\n\t\torg/example/Consumer ({consumer})\n"
+            )),
+            "{text}"
+        );
+        assert!(
+            text.ends_with("Missing packages\n\torg/apache/commons/math3/distribution, classes: 2, consumers: 1, archives: 1\n\torg/apache/commons/math3/random, classes: 2, consumers: 1, archives: 1\n\torg/apache/commons/math3/util, classes: 1, consumers: 1, archives: 1\n"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn api_diff() {
+        let pkg_path = env!("CARGO_MANIFEST_DIR");
+        let options = ParseOptions::default();
+        let old = parse_classpath(
+            &format!("{pkg_path}/testdata/api_diff_test_jar.jar"),
+            &options,
+        )
+        .unwrap();
+        let new = parse_classpath(
+            &format!("{pkg_path}/testdata/api_diff_v2_test_jar.jar"),
+            &options,
+        )
+        .unwrap();
+        let differences = api_checker::compare(&old.classes, &new.classes).unwrap();
+        assert_eq!(
+            api_checker::format(&differences),
+            "org/example/api/Extensible
+\tNow abstract (binary incompatible)
+\tMethod go()V is now final (binary incompatible)
+org/example/api/Handler
+\tMethod handle()V is now abstract (binary incompatible)
+\tAbstract method close()V added (binary compatible)
+org/example/api/Hidden
+\tNo longer public (binary incompatible)
+org/example/api/Listener
+\tAbstract method onError()V added (binary compatible)
+org/example/api/RemovedClass
+\tRemoved (binary incompatible)
+org/example/api/Shape
+\tClass is now an interface (binary incompatible)
+org/example/api/Widget
+\tNow final (binary incompatible)
+\tMethod render()V removed (binary incompatible)
+\tMethod size()I changed to size()J (binary incompatible)
+\tMethod resize(I)V changed from public to protected (binary incompatible)
+\tMethod reset()V is now static (binary incompatible)
+\tMethod create()Lorg/example/api/Widget; is no longer static (binary incompatible)
+\tField name:Ljava/lang/String; removed (binary incompatible)
+\tField count:I changed to count:J (binary incompatible)
+\tField level:I changed from protected to package-private (binary incompatible)
+\tField total:I is no longer static (binary incompatible)
+\tField limit:I is now final (binary incompatible)
+"
+        );
+        assert_eq!(
+            differences.iter().filter(|d| d.binary_incompatible).count(),
+            17
+        );
+    }
+
     fn load_classinfo(pkg_path: &str, version: u16) -> String {
         let mut classinfo_path = pkg_path.to_owned();
         classinfo_path.push_str(format!("/data/{version}.classinfo").as_str());
         let classinfo_path = classinfo_path.as_str();
         std::fs::read_to_string(classinfo_path).unwrap()
     }
+
+    fn decode(code: Vec<u8>) -> Result<Vec<Instruction>, String> {
+        Code {
+            max_stack: 0,
+            max_locals: 0,
+            code,
+            exception_table: vec![],
+            attributes: vec![],
+        }
+        .instructions()
+    }
+
+    fn switch_operands(values: &[i32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn wide_instructions() {
+        //wide iload 256, wide iinc 256 by -2, iinc 1 by -1
+        let code = vec![
+            code::WIDE,
+            0x15,
+            0x01,
+            0x00,
+            code::WIDE,
+            code::IINC,
+            0x01,
+            0x00,
+            0xff,
+            0xfe,
+            code::IINC,
+            0x01,
+            0xff,
+        ];
+        assert_eq!(
+            decode(code).unwrap(),
+            [
+                Instruction {
+                    pc: 0,
+                    opcode: 0x15,
+                    wide: true,
+                    operand: Operand::Local(256),
+                },
+                Instruction {
+                    pc: 4,
+                    opcode: code::IINC,
+                    wide: true,
+                    operand: Operand::Increment {
+                        local: 256,
+                        value: -2,
+                    },
+                },
+                Instruction {
+                    pc: 10,
+                    opcode: code::IINC,
+                    wide: false,
+                    operand: Operand::Increment {
+                        local: 1,
+                        value: -1,
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            decode(vec![code::WIDE, code::BIPUSH, 0x01]),
+            Err("Invalid wide opcode 0x10 at 0".to_owned())
+        );
+    }
+
+    #[test]
+    fn switch_padding() {
+        const RETURN: u8 = 0xb1;
+        //the operands start at a multiple of four, whatever the pc of the switch
+        for pc in 0..4 {
+            let padding = vec![0; 3 - pc % 4];
+            let table = [vec![0; pc], vec![code::TABLESWITCH], padding.clone()].concat();
+            let code = [
+                table,
+                switch_operands(&[10, -1, 1, 20, 30, 40]),
+                vec![RETURN],
+            ]
+            .concat();
+            let instructions = decode(code).unwrap();
+            assert_eq!(instructions.len(), pc + 2);
+            assert_eq!(instructions[pc].pc, pc);
+            assert_eq!(
+                instructions[pc].operand,
+                Operand::TableSwitch {
+                    default: 10,
+                    low: -1,
+                    offsets: vec![20, 30, 40],
+                }
+            );
+            assert_eq!(instructions[pc + 1].pc, pc + 1 + padding.len() + 24);
+            assert_eq!(instructions[pc + 1].opcode, RETURN);
+
+            let lookup = [vec![0; pc], vec![code::LOOKUPSWITCH], padding.clone()].concat();
+            let code = [
+                lookup,
+                switch_operands(&[10, 2, -1, 8, 5, 12]),
+                vec![RETURN],
+            ]
+            .concat();
+            let instructions = decode(code).unwrap();
+            assert_eq!(instructions.len(), pc + 2);
+            assert_eq!(
+                instructions[pc].operand,
+                Operand::LookupSwitch {
+                    default: 10,
+                    pairs: vec![(-1, 8), (5, 12)],
+                }
+            );
+            assert_eq!(instructions[pc + 1].pc, pc + 1 + padding.len() + 24);
+        }
+    }
+
+    #[test]
+    fn invalid_opcodes() {
+        assert_eq!(
+            decode(vec![0x00, 0xca]),
+            Err("Invalid opcode 0xca at 1".to_owned())
+        );
+        assert_eq!(
+            decode(vec![0xff]),
+            Err("Invalid opcode 0xff at 0".to_owned())
+        );
+        //high below low
+        let code = [
+            vec![code::TABLESWITCH, 0, 0, 0],
+            switch_operands(&[0, 2, 1]),
+        ]
+        .concat();
+        assert_eq!(
+            decode(code),
+            Err("Invalid tableswitch range at 0".to_owned())
+        );
+        let code = [vec![code::LOOKUPSWITCH, 0, 0, 0], switch_operands(&[0, -1])].concat();
+        assert_eq!(
+            decode(code),
+            Err("Invalid lookupswitch pair count at 0".to_owned())
+        );
+    }
+
+    #[test]
+    fn truncated_code() {
+        for (code, pos) in [
+            (vec![code::SIPUSH, 0x01], 1),
+            (vec![code::GOTO_W, 0x00, 0x00, 0x00], 1),
+            (vec![code::INVOKEINTERFACE, 0x00, 0x01, 0x01], 4),
+            (vec![code::WIDE], 1),
+            (vec![code::WIDE, code::IINC, 0x00, 0x01, 0x00], 4),
+            //the padding reaches the end of the code
+            (vec![0x00, code::TABLESWITCH], 4),
+            //the second offset is missing
+            (
+                [
+                    vec![code::TABLESWITCH, 0, 0, 0],
+                    switch_operands(&[0, 0, 1, 8]),
+                ]
+                .concat(),
+                20,
+            ),
+            //the second pair lacks its offset
+            (
+                [
+                    vec![code::LOOKUPSWITCH, 0, 0, 0],
+                    switch_operands(&[0, 2, 1, 8, 2]),
+                ]
+                .concat(),
+                24,
+            ),
+        ] {
+            assert_eq!(decode(code), Err(format!("Truncated instruction at {pos}")));
+        }
+    }
 }
@@ -0,0 +1,155 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+// the code generated by modular_bitfield_msb trips these lints
+#![allow(unused_parens, clippy::new_without_default)]
+
+use binrw::prelude::*;
+use modular_bitfield_msb::prelude::*;
+
+/*
+ * 2 Bytes = 0x0000
+ * 0000 0000 0000 0000
+ * ||||  ||    ||    1 public
+ * ||||  ||    |1      final
+ * ||||  ||    1       super
+ * ||||  |1            interface
+ * ||||  1             abstract
+ * |||1                synthetic
+ * ||1                 annotation
+ * |1                  enum
+ * 1                   module
+ */
+#[bitfield(bytes = 2)]
+#[derive(Debug, BinRead)]
+#[br(map = Self::from_bytes)]
+pub struct ClassFlags {
+    #[skip(setters)]
+    pub(crate) _module: bool,
+    #[skip(setters)]
+    pub(crate) _is_enum: bool,
+    #[skip(setters)]
+    pub(crate) _annotation: bool,
+    #[skip(setters)]
+    pub(crate) _synthetic: bool,
+    #[skip]
+    __: B1,
+    #[skip(setters)]
+    pub(crate) _is_abstract: bool,
+    #[skip(setters)]
+    pub(crate) _interface: bool,
+    #[skip]
+    __: B3,
+    #[skip(setters)]
+    pub(crate) _is_super: bool,
+    #[skip(setters)]
+    pub(crate) _is_final: bool,
+    #[skip]
+    __: B3,
+    #[skip(setters)]
+    pub(crate) _public: bool,
+}
+
+/*
+ * 0000 0000 0000 0000
+ *  | |      || ||||1 public
+ *  | |      || |||1  private
+ *  | |      || ||1   protected
+ *  | |      || |1    static
+ *  | |      || 1     final
+ *  | |      |1       volatile
+ *  | |      1        transient
+ *  | 1               synthetic
+ *  1                 enum
+ */
+#[bitfield(bytes = 2)]
+#[derive(Debug, BinRead)]
+#[br(map = Self::from_bytes)]
+pub struct FieldFlags {
+    #[skip]
+    __: B1,
+    #[skip(setters)]
+    pub(crate) _is_enum: bool,
+    #[skip]
+    __: B1,
+    #[skip(setters)]
+    pub(crate) _synthetic: bool,
+
+    #[skip]
+    __: B4,
+
+    #[skip(setters)]
+    pub(crate) _is_transient: bool,
+    #[skip(setters)]
+    pub(crate) _is_volatile: bool,
+    #[skip]
+    __: B1,
+    #[skip(setters)]
+    pub(crate) _is_final: bool,
+
+    #[skip(setters)]
+    pub(crate) _is_static: bool,
+    #[skip(setters)]
+    pub(crate) _is_protected: bool,
+    #[skip(setters)]
+    pub(crate) _is_private: bool,
+    #[skip(setters)]
+    pub(crate) _is_public: bool,
+}
+
+/*
+ * 0000 0000 0000 0000
+ *    | || | |||| |||1 public
+ *    | || | |||| ||1  private
+ *    | || | |||| |1   protected
+ *    | || | |||| 1    static
+ *    | || | |||1      final
+ *    | || | ||1       synchronized
+ *    | || | |1        bridge
+ *    | || | 1         varargs
+ *    | || 1           native
+ *    | |1             abstract
+ *    | 1              strict
+ *    1                synthetic
+ */
+#[bitfield(bytes = 2)]
+#[derive(Debug, BinRead)]
+#[br(map = Self::from_bytes)]
+pub struct MethodFlags {
+    #[skip]
+    __: B3,
+    #[skip(setters)]
+    pub(crate) _synthetic: bool,
+
+    #[skip(setters)]
+    pub(crate) _is_strict: bool,
+    #[skip(setters)]
+    pub(crate) _is_abstract: bool,
+    #[skip]
+    __: B1,
+    #[skip(setters)]
+    pub(crate) _is_native: bool,
+
+    #[skip(setters)]
+    pub(crate) _has_varargs: bool,
+    #[skip(setters)]
+    pub(crate) _is_bridge: bool,
+    #[skip(setters)]
+    pub(crate) _is_synchronized: bool,
+    #[skip(setters)]
+    pub(crate) _is_final: bool,
+
+    #[skip(setters)]
+    pub(crate) _is_static: bool,
+    #[skip(setters)]
+    pub(crate) _is_protected: bool,
+    #[skip(setters)]
+    pub(crate) _is_private: bool,
+    #[skip(setters)]
+    pub(crate) _is_public: bool,
+}
@@ -6,20 +6,22 @@
 * SPDX-License-Identifier: MPL-2.0
 */
 
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use nom::{
     IResult, Parser,
+    branch::alt,
     bytes::complete::{tag, take_till},
     character::complete::{char, line_ending, usize},
     combinator::opt,
     error::Error,
     multi::{many, many1, separated_list0},
-    sequence::{preceded, terminated},
+    sequence::{delimited, preceded, terminated},
 };
 
 use log::trace;
 
 type HashMap<K, V> = AHashMap<K, V>;
+type HashSet<E> = AHashSet<E>;
 
 #[derive(Debug)]
 pub struct ClassInfo<'a> {
@@ -28,6 +30,8 @@ pub struct ClassInfo<'a> {
     pub super_class: Option<&'a str>,
     pub interfaces: Vec<&'a str>,
     pub methods: HashMap<String, Method>,
+    //None if the classinfo file was created without field information
+    pub fields: Option<HashSet<String>>,
 }
 
 impl<'a> ClassInfo<'a> {
@@ -47,11 +51,21 @@ impl<'a> ClassInfo<'a> {
         let (remaining, is_interface) = interface.parse(remaining)?;
         let (remaining, super_class) = colon_terminated.parse(remaining)?;
         let (remaining, interfaces) = colon_terminated.parse(remaining)?;
-        let (remaining, methods_count) = terminated(usize, line_ending).parse(remaining)?;
+        let (remaining, (methods_count, fields_count)) =
+            terminated((usize, opt(preceded(char(':'), usize))), line_ending).parse(remaining)?;
 
         trace!("Parsing {} methods", methods_count);
         let (remaining, methods): (&str, Vec<Method>) =
             many(methods_count, method).parse(remaining)?;
+        let (remaining, fields) = match fields_count {
+            Some(fields_count) => {
+                trace!("Parsing {} fields", fields_count);
+                let (remaining, fields): (&str, Vec<String>) =
+                    many(fields_count, field).parse(remaining)?;
+                (remaining, Some(fields.into_iter().collect()))
+            }
+            None => (remaining, None),
+        };
         trace!(
             "Parsed Class {} with super {}, interfaces {}, and methods {:?}",
             class_name, super_class, interfaces, methods
@@ -74,6 +88,7 @@ impl<'a> ClassInfo<'a> {
                     .iter()
                     .map(|m| (m.signature.to_owned(), m.clone()))
                     .collect(),
+                fields,
             },
         ))
     }
@@ -90,13 +105,18 @@ impl<'a> ClassInfo<'a> {
 pub struct Method {
     pub signature: String,
     pub polymorphic_signature: bool,
+    //both None if the classinfo file was created without method flags
+    pub is_static: Option<bool>,
+    pub is_abstract: Option<bool>,
 }
 
 impl Method {
-    pub fn new(signature: String) -> Self {
+    pub fn new(signature: String, is_static: bool, is_abstract: bool) -> Self {
         Method {
             signature,
             polymorphic_signature: false,
+            is_static: Some(is_static),
+            is_abstract: Some(is_abstract),
         }
     }
 }
@@ -104,6 +124,16 @@ impl Method {
 fn method(input: &str) -> IResult<&str, Method> {
     let (remaining, signature) =
         preceded(tag("--"), take_till(|c| c == ':' || c == '\n')).parse(input)?;
+    //static, instance or abstract instance method
+    let (remaining, flags) = opt(preceded(
+        tag(":"),
+        alt((
+            tag("S").map(|_| (true, false)),
+            tag("I").map(|_| (false, false)),
+            tag("A").map(|_| (false, true)),
+        )),
+    ))
+    .parse(remaining)?;
     let (remaining, polymorphic_signature) =
         terminated(opt(preceded(tag(":"), tag("PS"))), line_ending).parse(remaining)?;
     Ok((
@@ -111,6 +141,18 @@ fn method(input: &str) -> IResult<&str, Method> {
         Method {
             signature: signature.to_owned(),
             polymorphic_signature: polymorphic_signature.is_some(),
+            is_static: flags.map(|(is_static, _)| is_static),
+            is_abstract: flags.map(|(_, is_abstract)| is_abstract),
         },
     ))
 }
+
+fn field(input: &str) -> IResult<&str, String> {
+    let (remaining, signature) = delimited(
+        tag("=="),
+        take_till(|c| c == '\n' || c == '\r'),
+        line_ending,
+    )
+    .parse(input)?;
+    Ok((remaining, signature.to_owned()))
+}
@@ -0,0 +1,372 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::io::{Read, Seek};
+
+use binrw::BinReaderExt;
+use binrw::prelude::*;
+
+use crate::java_class::{AttributeInfo, ClassUsage, MemberUsage};
+
+pub const BIPUSH: u8 = 0x10;
+pub const SIPUSH: u8 = 0x11;
+pub const LDC: u8 = 0x12;
+pub const LDC_W: u8 = 0x13;
+pub const LDC2_W: u8 = 0x14;
+pub const IINC: u8 = 0x84;
+pub const RET: u8 = 0xa9;
+pub const TABLESWITCH: u8 = 0xaa;
+pub const LOOKUPSWITCH: u8 = 0xab;
+pub const GETSTATIC: u8 = 0xb2;
+pub const PUTSTATIC: u8 = 0xb3;
+pub const GETFIELD: u8 = 0xb4;
+pub const PUTFIELD: u8 = 0xb5;
+pub const INVOKEVIRTUAL: u8 = 0xb6;
+pub const INVOKESPECIAL: u8 = 0xb7;
+pub const INVOKESTATIC: u8 = 0xb8;
+pub const INVOKEINTERFACE: u8 = 0xb9;
+pub const INVOKEDYNAMIC: u8 = 0xba;
+pub const NEW: u8 = 0xbb;
+pub const NEWARRAY: u8 = 0xbc;
+pub const ANEWARRAY: u8 = 0xbd;
+pub const CHECKCAST: u8 = 0xc0;
+pub const INSTANCEOF: u8 = 0xc1;
+pub const WIDE: u8 = 0xc4;
+pub const MULTIANEWARRAY: u8 = 0xc5;
+pub const IFNULL: u8 = 0xc6;
+pub const IFNONNULL: u8 = 0xc7;
+pub const GOTO_W: u8 = 0xc8;
+pub const JSR_W: u8 = 0xc9;
+
+/// The mnemonics of all opcodes, by opcode.
+#[rustfmt::skip]
+const MNEMONICS: [&str; 0xca] = [
+    "nop", "aconst_null", "iconst_m1", "iconst_0", "iconst_1", "iconst_2", "iconst_3",
+    "iconst_4", "iconst_5", "lconst_0", "lconst_1", "fconst_0", "fconst_1", "fconst_2",
+    "dconst_0", "dconst_1", "bipush", "sipush", "ldc", "ldc_w", "ldc2_w", "iload", "lload",
+    "fload", "dload", "aload", "iload_0", "iload_1", "iload_2", "iload_3", "lload_0", "lload_1",
+    "lload_2", "lload_3", "fload_0", "fload_1", "fload_2", "fload_3", "dload_0", "dload_1",
+    "dload_2", "dload_3", "aload_0", "aload_1", "aload_2", "aload_3", "iaload", "laload",
+    "faload", "daload", "aaload", "baload", "caload", "saload", "istore", "lstore", "fstore",
+    "dstore", "astore", "istore_0", "istore_1", "istore_2", "istore_3", "lstore_0", "lstore_1",
+    "lstore_2", "lstore_3", "fstore_0", "fstore_1", "fstore_2", "fstore_3", "dstore_0",
+    "dstore_1", "dstore_2", "dstore_3", "astore_0", "astore_1", "astore_2", "astore_3",
+    "iastore", "lastore", "fastore", "dastore", "aastore", "bastore", "castore", "sastore",
+    "pop", "pop2", "dup", "dup_x1", "dup_x2", "dup2", "dup2_x1", "dup2_x2", "swap", "iadd",
+    "ladd", "fadd", "dadd", "isub", "lsub", "fsub", "dsub", "imul", "lmul", "fmul", "dmul",
+    "idiv", "ldiv", "fdiv", "ddiv", "irem", "lrem", "frem", "drem", "ineg", "lneg", "fneg",
+    "dneg", "ishl", "lshl", "ishr", "lshr", "iushr", "lushr", "iand", "land", "ior", "lor",
+    "ixor", "lxor", "iinc", "i2l", "i2f", "i2d", "l2i", "l2f", "l2d", "f2i", "f2l", "f2d",
+    "d2i", "d2l", "d2f", "i2b", "i2c", "i2s", "lcmp", "fcmpl", "fcmpg", "dcmpl", "dcmpg",
+    "ifeq", "ifne", "iflt", "ifge", "ifgt", "ifle", "if_icmpeq", "if_icmpne", "if_icmplt",
+    "if_icmpge", "if_icmpgt", "if_icmple", "if_acmpeq", "if_acmpne", "goto", "jsr", "ret",
+    "tableswitch", "lookupswitch", "ireturn", "lreturn", "freturn", "dreturn", "areturn",
+    "return", "getstatic", "putstatic", "getfield", "putfield", "invokevirtual",
+    "invokespecial", "invokestatic", "invokeinterface", "invokedynamic", "new", "newarray",
+    "anewarray", "arraylength", "athrow", "checkcast", "instanceof", "monitorenter",
+    "monitorexit", "wide", "multianewarray", "ifnull", "ifnonnull", "goto_w", "jsr_w",
+];
+
+/// The Code attribute of a method.
+#[binread]
+#[derive(Debug)]
+pub struct Code {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    #[br(temp)]
+    code_length: u32,
+    #[br(count = code_length)]
+    pub code: Vec<u8>,
+    #[br(temp)]
+    exception_table_length: u16,
+    #[br(count = exception_table_length)]
+    pub exception_table: Vec<ExceptionHandler>,
+    #[br(temp)]
+    attributes_count: u16,
+    #[br(count = attributes_count)]
+    pub attributes: Vec<AttributeInfo>,
+}
+
+#[binread]
+#[derive(Debug)]
+pub struct ExceptionHandler {
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    //0 for a handler catching everything, as used by finally blocks
+    pub catch_type: u16,
+}
+
+/// A decoded instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    //offset of the opcode, or of the wide prefix, in the code
+    pub pc: usize,
+    //the modified opcode for wide instructions
+    pub opcode: u8,
+    pub wide: bool,
+    pub operand: Operand,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    None,
+    //bipush and sipush
+    Immediate(i16),
+    //loads, stores and ret
+    Local(u16),
+    Increment {
+        local: u16,
+        value: i16,
+    },
+    //ldc, field and method instructions but invokeinterface, new, anewarray, checkcast and
+    //instanceof
+    ConstPool(u16),
+    //invokeinterface, the count is redundant with the method descriptor
+    Interface {
+        index: u16,
+        count: u8,
+    },
+    //the type code of newarray
+    ArrayType(u8),
+    MultiNewArray {
+        index: u16,
+        dimensions: u8,
+    },
+    //relative to the pc of the instruction
+    Branch(i32),
+    TableSwitch {
+        default: i32,
+        low: i32,
+        offsets: Vec<i32>,
+    },
+    LookupSwitch {
+        default: i32,
+        pairs: Vec<(i32, i32)>,
+    },
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        MNEMONICS[self.opcode as usize]
+    }
+
+    /// The constant pool entry used by the instruction.
+    pub fn const_pool_index(&self) -> Option<u16> {
+        match self.operand {
+            Operand::ConstPool(index)
+            | Operand::Interface { index, .. }
+            | Operand::MultiNewArray { index, .. } => Some(index),
+            _ => None,
+        }
+    }
+}
+
+impl Code {
+    pub fn from<T>(data: &mut T) -> binrw::BinResult<Self>
+    where
+        T: Read + Seek,
+    {
+        data.read_be()
+    }
+
+    pub fn instructions(&self) -> Result<Vec<Instruction>, String> {
+        let mut reader = CodeReader {
+            code: &self.code,
+            pos: 0,
+        };
+        let mut result = Vec::new();
+        while reader.pos < self.code.len() {
+            result.push(reader.instruction()?);
+        }
+        Ok(result)
+    }
+}
+
+/// The constant pool indexes of the classes used by instructions, with the kind of usage.
+/// `ldc` and `ldc_w` are included for every index, they only name a class literal if the entry
+/// is a Class entry.
+pub fn class_operands(instructions: &[Instruction]) -> Vec<(u16, ClassUsage)> {
+    instructions
+        .iter()
+        .filter_map(|instruction| {
+            let usage = match instruction.opcode {
+                LDC | LDC_W => ClassUsage::ClassLiteral,
+                NEW => ClassUsage::New,
+                ANEWARRAY | MULTIANEWARRAY => ClassUsage::ArrayCreation,
+                CHECKCAST => ClassUsage::Checkcast,
+                INSTANCEOF => ClassUsage::Instanceof,
+                _ => return None,
+            };
+            Some((instruction.const_pool_index()?, usage))
+        })
+        .collect()
+}
+
+/// The constant pool indexes loaded by `ldc`, `ldc_w` and `ldc2_w`, and the call sites of
+/// `invokedynamic`. Method handles and bootstrap methods are only used through these.
+pub fn constant_operands(instructions: &[Instruction]) -> Vec<u16> {
+    instructions
+        .iter()
+        .filter(|instruction| matches!(instruction.opcode, LDC | LDC_W | LDC2_W | INVOKEDYNAMIC))
+        .filter_map(Instruction::const_pool_index)
+        .collect()
+}
+
+/// The constant pool indexes of the fields and methods used by instructions, with the
+/// instruction using them.
+pub fn member_operands(instructions: &[Instruction]) -> Vec<(u16, MemberUsage)> {
+    instructions
+        .iter()
+        .filter_map(|instruction| {
+            let usage = match instruction.opcode {
+                GETSTATIC => MemberUsage::GetStatic,
+                PUTSTATIC => MemberUsage::PutStatic,
+                GETFIELD => MemberUsage::GetField,
+                PUTFIELD => MemberUsage::PutField,
+                INVOKEVIRTUAL => MemberUsage::InvokeVirtual,
+                INVOKESPECIAL => MemberUsage::InvokeSpecial,
+                INVOKESTATIC => MemberUsage::InvokeStatic,
+                INVOKEINTERFACE => MemberUsage::InvokeInterface,
+                _ => return None,
+            };
+            Some((instruction.const_pool_index()?, usage))
+        })
+        .collect()
+}
+
+struct CodeReader<'a> {
+    code: &'a [u8],
+    pos: usize,
+}
+
+impl CodeReader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self
+            .code
+            .get(self.pos..self.pos + N)
+            .ok_or_else(|| format!("Truncated instruction at {}", self.pos))?;
+        self.pos += N;
+        Ok(bytes.try_into().expect("Slice of the wrong length"))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.bytes()?))
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        Ok(i16::from_be_bytes(self.bytes()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_be_bytes(self.bytes()?))
+    }
+
+    fn instruction(&mut self) -> Result<Instruction, String> {
+        let pc = self.pos;
+        let opcode = self.u8()?;
+        let operand = match opcode {
+            BIPUSH => Operand::Immediate(i16::from(self.u8()? as i8)),
+            SIPUSH => Operand::Immediate(self.i16()?),
+            LDC => Operand::ConstPool(u16::from(self.u8()?)),
+            LDC_W
+            | LDC2_W
+            | GETSTATIC..=INVOKESTATIC
+            | NEW
+            | ANEWARRAY
+            | CHECKCAST
+            | INSTANCEOF => Operand::ConstPool(self.u16()?),
+            INVOKEINTERFACE => {
+                let index = self.u16()?;
+                let count = self.u8()?;
+                //always zero
+                self.u8()?;
+                Operand::Interface { index, count }
+            }
+            INVOKEDYNAMIC => {
+                let index = self.u16()?;
+                //always zero
+                self.u16()?;
+                Operand::ConstPool(index)
+            }
+            0x15..=0x19 | 0x36..=0x3a | RET => Operand::Local(u16::from(self.u8()?)),
+            IINC => Operand::Increment {
+                local: u16::from(self.u8()?),
+                value: i16::from(self.u8()? as i8),
+            },
+            0x99..=0xa8 | IFNULL | IFNONNULL => Operand::Branch(i32::from(self.i16()?)),
+            GOTO_W | JSR_W => Operand::Branch(self.i32()?),
+            NEWARRAY => Operand::ArrayType(self.u8()?),
+            MULTIANEWARRAY => Operand::MultiNewArray {
+                index: self.u16()?,
+                dimensions: self.u8()?,
+            },
+            TABLESWITCH => {
+                self.align();
+                let default = self.i32()?;
+                let low = self.i32()?;
+                let high = self.i32()?;
+                if high < low {
+                    return Err(format!("Invalid tableswitch range at {pc}"));
+                }
+                let offsets = (low..=high).map(|_| self.i32()).collect::<Result<_, _>>()?;
+                Operand::TableSwitch {
+                    default,
+                    low,
+                    offsets,
+                }
+            }
+            LOOKUPSWITCH => {
+                self.align();
+                let default = self.i32()?;
+                let count = self.i32()?;
+                if count < 0 {
+                    return Err(format!("Invalid lookupswitch pair count at {pc}"));
+                }
+                let pairs = (0..count)
+                    .map(|_| Ok((self.i32()?, self.i32()?)))
+                    .collect::<Result<_, String>>()?;
+                Operand::LookupSwitch { default, pairs }
+            }
+            WIDE => {
+                let opcode = self.u8()?;
+                let operand = match opcode {
+                    0x15..=0x19 | 0x36..=0x3a | RET => Operand::Local(self.u16()?),
+                    IINC => Operand::Increment {
+                        local: self.u16()?,
+                        value: self.i16()?,
+                    },
+                    _ => return Err(format!("Invalid wide opcode {opcode:#04x} at {pc}")),
+                };
+                return Ok(Instruction {
+                    pc,
+                    opcode,
+                    wide: true,
+                    operand,
+                });
+            }
+            0x00..=0xc9 => Operand::None,
+            _ => return Err(format!("Invalid opcode {opcode:#04x} at {pc}")),
+        };
+        Ok(Instruction {
+            pc,
+            opcode,
+            wide: false,
+            operand,
+        })
+    }
+
+    /// Skips the padding of switches, which aligns their operands to four bytes.
+    fn align(&mut self) {
+        self.pos = (self.pos + 3) & !3;
+    }
+}
@@ -6,7 +6,11 @@
 * SPDX-License-Identifier: MPL-2.0
 */
 
-use std::{fmt::Display, io};
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
 
 macro_rules! define_errcodes {
     [ $( $name:ident : $class:ty ),+ ] => {
@@ -46,10 +50,84 @@ impl Display for Error {
     };
 }
 
+#[derive(Debug)]
+pub struct FormatError(pub String);
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+/// A class that could not be checked or compared, as its constant pool is broken.
+#[derive(Debug)]
+pub struct InvalidClass {
+    pub class: String,
+    pub cause: String,
+}
+
+impl InvalidClass {
+    pub fn new(class: &str, cause: String) -> Self {
+        InvalidClass {
+            class: class.to_owned(),
+            cause,
+        }
+    }
+}
+
+impl Display for InvalidClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.class, self.cause)
+    }
+}
+
+impl std::error::Error for InvalidClass {}
+
+/// An error that occurred while reading a JAR file or one of its entries.
+#[derive(Debug)]
+pub struct ArchiveError {
+    pub path: PathBuf,
+    pub entry: Option<String>,
+    pub cause: Box<Error>,
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.entry {
+            Some(entry) => write!(f, "{}!/{}: {}", self.path.display(), entry, self.cause),
+            None => write!(f, "{}: {}", self.path.display(), self.cause),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
+
 define_errcodes![
     IoError: io::Error,
     ParsingError: binrw::Error,
     PatternError: glob::PatternError,
     GlobError: glob::GlobError,
-    ZipError: zip::result::ZipError
+    ExpansionError: shellexpand::LookupError<std::env::VarError>,
+    ZipError: zip::result::ZipError,
+    FormatError: FormatError,
+    ArchiveError: ArchiveError
 ];
+
+impl Error {
+    pub(crate) fn in_archive(self, path: &Path, entry: Option<&str>) -> Self {
+        match self {
+            Error::ArchiveError(_) => self,
+            e => Error::ArchiveError(ArchiveError {
+                path: path.to_owned(),
+                entry: entry.map(str::to_owned),
+                cause: Box::new(e),
+            }),
+        }
+    }
+}
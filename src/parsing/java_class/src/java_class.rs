@@ -6,17 +6,150 @@
 * SPDX-License-Identifier: MPL-2.0
 */
 
-use std::io::{Read, Seek};
+use std::{
+    collections::BTreeSet,
+    fmt::Display,
+    io::{Cursor, Read, Seek, SeekFrom},
+};
 
 use ahash::AHashMap;
 use ahash::AHashSet;
 use binrw::BinReaderExt;
 use binrw::prelude::*;
-use modular_bitfield_msb::prelude::*;
+use serde::Serialize;
+
+pub use crate::access_flags::{ClassFlags, FieldFlags, MethodFlags};
+use crate::code::{self, Code};
 
 type HashMap<K, V> = AHashMap<K, V>;
 type HashSet<E> = AHashSet<E>;
 
+/// How a class uses another one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassUsage {
+    //owner of a referenced method or field
+    MemberReference,
+    Superclass,
+    Interface,
+    New,
+    Checkcast,
+    Instanceof,
+    //anewarray and multianewarray
+    ArrayCreation,
+    //ldc of the class
+    ClassLiteral,
+    //type of an exception handler
+    CatchType,
+}
+
+/// The instruction using a field or method, or the kind of a method handle referencing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberUsage {
+    GetField,
+    PutField,
+    GetStatic,
+    PutStatic,
+    InvokeVirtual,
+    //constructors, private and super methods, and method handles creating instances
+    InvokeSpecial,
+    InvokeStatic,
+    InvokeInterface,
+}
+
+impl MemberUsage {
+    /// Whether the usage requires a static member.
+    pub fn is_static(self) -> bool {
+        matches!(
+            self,
+            MemberUsage::GetStatic | MemberUsage::PutStatic | MemberUsage::InvokeStatic
+        )
+    }
+
+    /// The usage equivalent to the reference kind of a method handle.
+    fn of_handle(ref_kind: u8) -> Option<Self> {
+        Some(match ref_kind {
+            1 => MemberUsage::GetField,
+            2 => MemberUsage::GetStatic,
+            3 => MemberUsage::PutField,
+            4 => MemberUsage::PutStatic,
+            5 => MemberUsage::InvokeVirtual,
+            6 => MemberUsage::InvokeStatic,
+            7 | 8 => MemberUsage::InvokeSpecial,
+            9 => MemberUsage::InvokeInterface,
+            _ => return None,
+        })
+    }
+}
+
+/// The access level of a class member, from the least to the most accessible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    Private,
+    Package,
+    Protected,
+    Public,
+}
+
+impl Access {
+    fn of(public: bool, protected: bool, private: bool) -> Self {
+        if public {
+            Access::Public
+        } else if protected {
+            Access::Protected
+        } else if private {
+            Access::Private
+        } else {
+            Access::Package
+        }
+    }
+}
+
+impl Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Access::Private => write!(f, "private"),
+            Access::Package => write!(f, "package-private"),
+            Access::Protected => write!(f, "protected"),
+            Access::Public => write!(f, "public"),
+        }
+    }
+}
+
+/// The used constant pool entries of a class, by index.
+#[derive(Debug, Default)]
+pub struct Usages {
+    pub classes: HashMap<u16, BTreeSet<ClassUsage>>,
+    pub members: HashMap<u16, BTreeSet<MemberUsage>>,
+}
+
+impl Usages {
+    fn add_class(&mut self, index: u16, usage: ClassUsage) {
+        self.classes.entry(index).or_default().insert(usage);
+    }
+
+    fn add_member(&mut self, index: u16, usage: MemberUsage) {
+        self.members.entry(index).or_default().insert(usage);
+    }
+}
+
+impl Display for MemberUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MemberUsage::GetField => write!(f, "getfield"),
+            MemberUsage::PutField => write!(f, "putfield"),
+            MemberUsage::GetStatic => write!(f, "getstatic"),
+            MemberUsage::PutStatic => write!(f, "putstatic"),
+            MemberUsage::InvokeVirtual => write!(f, "invokevirtual"),
+            MemberUsage::InvokeSpecial => write!(f, "invokespecial"),
+            MemberUsage::InvokeStatic => write!(f, "invokestatic"),
+            MemberUsage::InvokeInterface => write!(f, "invokeinterface"),
+        }
+    }
+}
+
 #[binread]
 #[derive(Debug)]
 #[br(magic = 0xCAFEBABEu32)]
@@ -37,7 +170,7 @@ pub struct Class {
     #[br(temp)]
     fields_count: u16,
     #[br(count = fields_count)]
-    _fields: Vec<FieldInfo>,
+    pub fields: Vec<FieldInfo>,
     #[br(temp)]
     methods_count: u16,
     #[br(count = methods_count)]
@@ -49,15 +182,19 @@ pub struct Class {
 }
 
 impl Class {
-    pub fn from<T>(data: &mut T) -> Self
+    pub fn from<T>(data: &mut T) -> binrw::BinResult<Self>
     where
         T: Read + Seek,
     {
-        data.read_be().unwrap()
+        //the backtrace is only useful for debugging the parser and renders over many lines
+        data.read_be().map_err(|e| match e {
+            binrw::Error::Backtrace(backtrace) => *backtrace.error,
+            e => e,
+        })
     }
 
     pub fn get_utf8<'a>(&'a self, index: &u16) -> Result<&'a str, String> {
-        if let ConstPoolEntry::Utf8 { value } = &self.const_pool[index] {
+        if let Some(ConstPoolEntry::Utf8 { value }) = self.const_pool.get(index) {
             Ok(value.as_str())
         } else {
             Err(format!("Not a UTF8 entry at idx {index}!"))
@@ -73,9 +210,45 @@ impl Class {
         Ok(result)
     }
 
+    pub fn get_fields(&self) -> Result<HashSet<String>, String> {
+        let mut result = HashSet::new();
+        for field_info in &self.fields {
+            let field_name = self.get_utf8(&field_info.name_index)?;
+            let field_descriptor = self.get_utf8(&field_info.descriptor_index)?;
+            result.insert(format!("{field_name}:{field_descriptor}"));
+        }
+        Ok(result)
+    }
+
+    pub fn get_constant_value(&self, field: &FieldInfo) -> Result<Option<&ConstPoolEntry>, String> {
+        for attribute in &field.attributes {
+            if self.get_utf8(&attribute._name_index)? != "ConstantValue" {
+                continue;
+            }
+            let [high, low] = attribute._data[..] else {
+                return Err(format!(
+                    "Invalid ConstantValue attribute length {}",
+                    attribute._data.len()
+                ));
+            };
+            let index = u16::from_be_bytes([high, low]);
+            return match self.const_pool.get(&index) {
+                Some(
+                    entry @ (ConstPoolEntry::Int { .. }
+                    | ConstPoolEntry::Float { .. }
+                    | ConstPoolEntry::Long { .. }
+                    | ConstPoolEntry::Double { .. }
+                    | ConstPoolEntry::String { .. }),
+                ) => Ok(Some(entry)),
+                _ => Err(format!("Not a constant value entry at idx {index}!")),
+            };
+        }
+        Ok(None)
+    }
+
     pub fn get_name(&self) -> Result<&str, String> {
-        let this_class = &self.const_pool[&self.this_class_idx];
-        let ConstPoolEntry::Class { name_index } = this_class else {
+        let this_class = self.const_pool.get(&self.this_class_idx);
+        let Some(ConstPoolEntry::Class { name_index }) = this_class else {
             return Err(format!(
                 "Not a Class entry at index {}",
                 self.this_class_idx
@@ -84,37 +257,154 @@ impl Class {
         self.get_utf8(name_index)
     }
 
-    pub fn is_class_entry_used(&self, const_pool_idx: &u16) -> bool {
-        for entry in self.const_pool.values() {
-            match entry {
-                ConstPoolEntry::MethodRef {
-                    class_index,
-                    name_type_index: _,
-                } => {
-                    if class_index == const_pool_idx {
-                        return true;
-                    }
+    /// How the Class, Fieldref, Methodref and InterfaceMethodref entries of the constant pool
+    /// are used. Entries only named by attributes like InnerClasses are left out, as loading
+    /// and running the class does not need them, and so are member references no code uses.
+    pub fn get_usages(&self) -> Result<Usages, String> {
+        let mut usages = Usages::default();
+        //loaded constants and call sites, which lead to method handles
+        let mut constants = vec![];
+        if self.super_class_idx != 0 {
+            usages.add_class(self.super_class_idx, ClassUsage::Superclass);
+        }
+        for index in &self.iface_indexes {
+            usages.add_class(*index, ClassUsage::Interface);
+        }
+        for method in &self.methods {
+            let Some(code) = method.get_code(self)? else {
+                continue;
+            };
+            let instructions = code.instructions()?;
+            for (index, usage) in code::class_operands(&instructions) {
+                if let Some(ConstPoolEntry::Class { .. }) = self.const_pool.get(&index) {
+                    usages.add_class(index, usage);
                 }
-                ConstPoolEntry::FieldRef {
-                    class_index,
-                    name_type_index: _,
-                } => {
-                    if class_index == const_pool_idx {
-                        return true;
+            }
+            for (index, usage) in code::member_operands(&instructions) {
+                usages.add_member(index, usage);
+            }
+            constants.extend(code::constant_operands(&instructions));
+            for handler in &code.exception_table {
+                if handler.catch_type != 0 {
+                    usages.add_class(handler.catch_type, ClassUsage::CatchType);
+                }
+            }
+        }
+        //a bootstrap method no call site or dynamic constant refers to uses nothing
+        let bootstrap_methods = self.get_bootstrap_methods()?;
+        let mut visited = HashSet::default();
+        while let Some(index) = constants.pop() {
+            if !visited.insert(index) {
+                continue;
+            }
+            match self.const_pool.get(&index) {
+                Some(ConstPoolEntry::MethodHandle {
+                    ref_kind,
+                    ref_index,
+                }) => {
+                    if let Some(usage) = MemberUsage::of_handle(*ref_kind) {
+                        usages.add_member(*ref_index, usage);
                     }
                 }
-                ConstPoolEntry::IfaceMethodRef {
-                    class_index,
-                    name_type_index: _,
-                } => {
-                    if class_index == const_pool_idx {
-                        return true;
+                Some(
+                    ConstPoolEntry::InvokeDynamic {
+                        bootstrap_index, ..
                     }
+                    | ConstPoolEntry::Dynamic {
+                        bootstrap_index, ..
+                    },
+                ) => {
+                    let bootstrap = bootstrap_methods
+                        .get(*bootstrap_index as usize)
+                        .ok_or_else(|| format!("No bootstrap method {bootstrap_index}"))?;
+                    constants.push(bootstrap.method_ref);
+                    constants.extend(&bootstrap.arguments);
                 }
-                _ => (),
+                _ => {}
+            }
+        }
+        let owners: Vec<u16> = usages
+            .members
+            .keys()
+            .filter_map(|index| match self.const_pool.get(index) {
+                Some(
+                    ConstPoolEntry::MethodRef { class_index, .. }
+                    | ConstPoolEntry::FieldRef { class_index, .. }
+                    | ConstPoolEntry::IfaceMethodRef { class_index, .. },
+                ) => Some(*class_index),
+                _ => None,
+            })
+            .collect();
+        for owner in owners {
+            usages.add_class(owner, ClassUsage::MemberReference);
+        }
+        Ok(usages)
+    }
+
+    /// The entries of the BootstrapMethods attribute, empty if the class has none.
+    pub fn get_bootstrap_methods(&self) -> Result<Vec<BootstrapMethod>, String> {
+        for attribute in &self.attributes {
+            if attribute.get_name(self)? == "BootstrapMethods" {
+                let methods: BootstrapMethods = Cursor::new(attribute.data())
+                    .read_be()
+                    .map_err(|e| format!("Invalid BootstrapMethods attribute: {e}"))?;
+                return Ok(methods.methods);
+            }
+        }
+        Ok(vec![])
+    }
+
+    /// The name of the superclass, `None` for `java/lang/Object` and modules.
+    pub fn get_super_name(&self) -> Result<Option<&str>, String> {
+        match self.const_pool.get(&self.super_class_idx) {
+            Some(ConstPoolEntry::Class { name_index }) => self.get_utf8(name_index).map(Some),
+            _ if self.super_class_idx == 0 => Ok(None),
+            _ => Err(format!(
+                "Not a Class entry at index {}",
+                self.super_class_idx
+            )),
+        }
+    }
+
+    pub fn get_interface_names(&self) -> Result<Vec<&str>, String> {
+        self.iface_indexes
+            .iter()
+            .map(|index| match self.const_pool.get(index) {
+                Some(ConstPoolEntry::Class { name_index }) => self.get_utf8(name_index),
+                _ => Err(format!("Not a Class entry at index {index}")),
+            })
+            .collect()
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.flags._public()
+    }
+
+    /// The host of the nest the class belongs to, which is the class itself unless it has a
+    /// NestHost attribute. Members of a nest may access each other's private members.
+    pub fn get_nest_host(&self) -> Result<&str, String> {
+        for attribute in &self.attributes {
+            if attribute.get_name(self)? != "NestHost" {
+                continue;
             }
+            let &[high, low] = attribute.data() else {
+                return Err("Invalid NestHost attribute".to_owned());
+            };
+            let index = u16::from_be_bytes([high, low]);
+            return match self.const_pool.get(&index) {
+                Some(ConstPoolEntry::Class { name_index }) => self.get_utf8(name_index),
+                _ => Err(format!("Not a Class entry at index {index}")),
+            };
         }
-        false
+        self.get_name()
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.flags._is_final()
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self.flags._is_abstract()
     }
 
     pub fn is_interface(&self) -> bool {
@@ -126,62 +416,55 @@ impl Class {
     }
 }
 
-/*
- * 2 Bytes = 0x0000
- * 0000 0000 0000 0000
- * ||||  ||    ||    1 public
- * ||||  ||    |1      final
- * ||||  ||    1       super
- * ||||  |1            interface
- * ||||  1             abstract
- * |||1                synthetic
- * ||1                 annotation
- * |1                  enum
- * 1                   module
- */
-#[allow(clippy::new_without_default)]
-#[bitfield(bytes = 2)]
-#[derive(Debug, BinRead)]
-#[br(map = Self::from_bytes)]
-pub struct ClassFlags {
-    #[skip(setters)]
-    _module: bool,
-    #[skip(setters)]
-    _is_enum: bool,
-    #[skip(setters)]
-    _annotation: bool,
-    #[skip(setters)]
-    _synthetic: bool,
-    #[skip]
-    __: B1,
-    #[skip(setters)]
-    _is_abstract: bool,
-    #[skip(setters)]
-    _interface: bool,
-    #[skip]
-    __: B3,
-    #[skip(setters)]
-    _is_super: bool,
-    #[skip(setters)]
-    _is_final: bool,
-    #[skip]
-    __: B3,
-    #[skip(setters)]
-    _public: bool,
-}
-
 fn read_utf8_lossy(data: Vec<u8>) -> String {
     match String::from_utf8(data) {
         Ok(s) => s,
-        Err(_) => "N/A".to_owned(),
+        Err(e) => read_modified_utf8(e.as_bytes()).unwrap_or_else(|| "N/A".to_owned()),
     }
 }
 
+//the JVM encodes NUL and supplementary characters differently from standard UTF-8
+fn read_modified_utf8(data: &[u8]) -> Option<String> {
+    let mut units: Vec<u16> = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let unit = match data[i] {
+            b @ 0x01..=0x7F => {
+                i += 1;
+                b as u16
+            }
+            b if b & 0xE0 == 0xC0 => {
+                let b2 = *data.get(i + 1).filter(|b| *b & 0xC0 == 0x80)?;
+                i += 2;
+                ((b as u16 & 0x1F) << 6) | (b2 as u16 & 0x3F)
+            }
+            b if b & 0xF0 == 0xE0 => {
+                let b2 = *data.get(i + 1).filter(|b| *b & 0xC0 == 0x80)?;
+                let b3 = *data.get(i + 2).filter(|b| *b & 0xC0 == 0x80)?;
+                i += 3;
+                ((b as u16 & 0x0F) << 12) | ((b2 as u16 & 0x3F) << 6) | (b3 as u16 & 0x3F)
+            }
+            _ => return None,
+        };
+        units.push(unit);
+    }
+    Some(String::from_utf16_lossy(&units))
+}
+
 #[binrw::parser(reader, endian)]
 fn parse_const_pool(count: u16) -> binrw::BinResult<HashMap<u16, ConstPoolEntry>> {
     let mut result = HashMap::with_capacity(count as usize);
     let mut i = 1;
     while i < count {
+        let pos = reader.stream_position()?;
+        let tag = u8::read_options(reader, endian, ())?;
+        if !ConstPoolEntry::TAGS.contains(&tag) {
+            return Err(binrw::Error::AssertFail {
+                pos,
+                message: format!("Unknown constant pool tag {tag} at index {i}"),
+            });
+        }
+        reader.seek(SeekFrom::Start(pos))?;
         let val = ConstPoolEntry::read_options(reader, endian, ())?;
         //doubles and longs take up two indices, so we manually advance them one further
         let next_i = match val {
@@ -240,6 +523,11 @@ pub enum ConstPoolEntry {
     MethodHandle { ref_kind: u8, ref_index: u16 },
     #[br(magic = 0x10u8)]
     MethodType { descriptor_index: u16 },
+    #[br(magic = 0x11u8)]
+    Dynamic {
+        bootstrap_index: u16,
+        name_type_index: u16,
+    },
     #[br(magic = 0x12u8)]
     InvokeDynamic {
         bootstrap_index: u16,
@@ -251,16 +539,62 @@ pub enum ConstPoolEntry {
     Package { name_index: u16 },
 }
 
+impl ConstPoolEntry {
+    const TAGS: [u8; 17] = [1, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 15, 16, 17, 18, 19, 20];
+}
+
 #[binread]
 #[derive(Debug)]
 pub struct FieldInfo {
-    _flags: u16, //bitfield
-    _name_index: u16,
-    _descriptor_index: u16,
+    pub flags: FieldFlags,
+    pub name_index: u16,
+    pub descriptor_index: u16,
     #[br(temp)]
     attributes_count: u16,
     #[br(count = attributes_count)]
-    _attributes: Vec<AttributeInfo>,
+    pub attributes: Vec<AttributeInfo>,
+}
+
+impl FieldInfo {
+    pub fn access(&self) -> Access {
+        Access::of(self.is_public(), self.is_protected(), self.is_private())
+    }
+
+    pub fn is_public(&self) -> bool {
+        self.flags._is_public()
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.flags._is_private()
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.flags._is_protected()
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.flags._is_static()
+    }
+
+    pub fn is_final(&self) -> bool {
+        self.flags._is_final()
+    }
+
+    pub fn is_volatile(&self) -> bool {
+        self.flags._is_volatile()
+    }
+
+    pub fn is_transient(&self) -> bool {
+        self.flags._is_transient()
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self.flags._synthetic()
+    }
+
+    pub fn is_enum(&self) -> bool {
+        self.flags._is_enum()
+    }
 }
 
 #[binread]
@@ -275,56 +609,78 @@ pub struct MethodInfo {
     _attributes: Vec<AttributeInfo>,
 }
 
-/*
- * 0000 0000 0000 0000
- *    | || | |||| |||1 public
- *    | || | |||| ||1  private
- *    | || | |||| |1   protected
- *    | || | |||| 1    static
- *    | || | |||1      final
- *    | || | ||1       synchronized
- *    | || | |1        bridge
- *    | || | 1         varargs
- *    | || 1           native
- *    | |1             abstract
- *    | 1              strict
- *    1                synthetic
- */
-#[bitfield(bytes = 2)]
-#[derive(Debug, BinRead)]
-#[br(map = Self::from_bytes)]
-pub struct MethodFlags {
-    #[skip]
-    __: B3,
-    #[skip(setters)]
-    _synthetic: bool,
-
-    #[skip(setters)]
-    _is_strict: bool,
-    #[skip(setters)]
-    _is_abstract: bool,
-    #[skip]
-    __: B1,
-    #[skip(setters)]
-    _is_native: bool,
-
-    #[skip(setters)]
-    _has_varargs: bool,
-    #[skip(setters)]
-    _is_bridge: bool,
-    #[skip(setters)]
-    _is_synchronized: bool,
-    #[skip(setters)]
-    _is_final: bool,
-
-    #[skip(setters)]
-    _is_static: bool,
-    #[skip(setters)]
-    _is_protected: bool,
-    #[skip(setters)]
-    _is_private: bool,
-    #[skip(setters)]
-    _is_public: bool,
+impl MethodInfo {
+    pub fn attributes(&self) -> &[AttributeInfo] {
+        &self._attributes
+    }
+
+    /// The Code attribute, `None` for abstract and native methods.
+    pub fn get_code(&self, class: &Class) -> Result<Option<Code>, String> {
+        for attribute in &self._attributes {
+            if attribute.get_name(class)? == "Code" {
+                return Code::from(&mut Cursor::new(&attribute._data))
+                    .map(Some)
+                    .map_err(|e| format!("Invalid Code attribute: {e}"));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn access(&self) -> Access {
+        Access::of(self.is_public(), self.is_protected(), self.is_private())
+    }
+
+    pub fn is_public(&self) -> bool {
+        self._flags._is_public()
+    }
+
+    pub fn is_private(&self) -> bool {
+        self._flags._is_private()
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self._flags._is_protected()
+    }
+
+    pub fn is_static(&self) -> bool {
+        self._flags._is_static()
+    }
+
+    pub fn is_final(&self) -> bool {
+        self._flags._is_final()
+    }
+
+    pub fn is_abstract(&self) -> bool {
+        self._flags._is_abstract()
+    }
+
+    pub fn is_bridge(&self) -> bool {
+        self._flags._is_bridge()
+    }
+
+    pub fn is_synthetic(&self) -> bool {
+        self._flags._synthetic()
+    }
+}
+
+#[binread]
+struct BootstrapMethods {
+    #[br(temp)]
+    count: u16,
+    #[br(count = count)]
+    methods: Vec<BootstrapMethod>,
+}
+
+/// An entry of the BootstrapMethods attribute.
+#[binread]
+#[derive(Debug)]
+pub struct BootstrapMethod {
+    //the MethodHandle entry of the bootstrap method
+    pub method_ref: u16,
+    #[br(temp)]
+    argument_count: u16,
+    #[br(count = argument_count)]
+    pub arguments: Vec<u16>,
 }
 
 #[binread]
@@ -336,3 +692,13 @@ pub struct AttributeInfo {
     #[br(count = length)]
     _data: Vec<u8>,
 }
+
+impl AttributeInfo {
+    pub fn get_name<'a>(&self, class: &'a Class) -> Result<&'a str, String> {
+        class.get_utf8(&self._name_index)
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self._data
+    }
+}
@@ -7,109 +7,718 @@
 */
 
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
     fs::File,
-    io::{Cursor, Read},
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{Cursor, Read, Seek},
     path::{Path, PathBuf},
 };
 
-use ahash::AHashMap;
-use java_class::{Class, ConstPoolEntry};
-use log::{debug, info};
+use ahash::{AHashMap, AHashSet};
+use error::{ArchiveError, FormatError};
+use java_class::Class;
+use log::{debug, info, trace, warn};
+use manifest::{MANIFEST_PATH, Manifest};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
 use zip::ZipArchive;
 
+mod access_flags;
 pub mod classinfo;
+pub mod code;
 pub mod error;
 pub mod java_class;
+pub mod manifest;
 
 type HashMap<K, V> = AHashMap<K, V>;
+type HashSet<K> = AHashSet<K>;
 type Result<T> = std::result::Result<T, error::Error>;
 
-fn read_zip_archive(path: &Path) -> Result<HashMap<String, Class>> {
-    debug!("Processing file {}...", path.to_str().unwrap());
-    let file = File::options()
+#[derive(Debug, Default)]
+pub struct Classpath {
+    pub classes: HashMap<String, Class>,
+    //where every class was read from
+    pub origins: HashMap<String, ClassOrigin>,
+    //classes found in more than one place, by class name
+    pub duplicates: BTreeMap<String, DuplicateClass>,
+    //JARs and classes that could not be read, only filled when keeping going
+    pub failures: Vec<ArchiveError>,
+    //versions found under META-INF/versions/ of multi-release JARs
+    pub releases: BTreeSet<u16>,
+    //Class-Path manifest entries pointing to files that do not exist
+    pub missing_references: Vec<MissingReference>,
+    //digests of the class files, used to tell duplicates apart
+    digests: HashMap<String, u64>,
+}
+
+/// The archive, class directory or class file a class was read from, and the path of the
+/// class inside of it. Nested archives are given as `outer.jar!/inner.jar`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ClassOrigin {
+    pub archive: PathBuf,
+    pub entry: String,
+}
+
+impl Display for ClassOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}!/{}", self.archive.display(), self.entry)
+    }
+}
+
+/// A class found in more than one class path element.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateClass {
+    //the class is loaded from the first archive, all others are shadowed
+    pub archives: Vec<PathBuf>,
+    pub methods_differ: bool,
+    pub bytes_differ: bool,
+}
+
+/// A `Class-Path` manifest entry that could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingReference {
+    pub jar: PathBuf,
+    pub reference: String,
+}
+
+impl Display for MissingReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.jar.display(), self.reference)
+    }
+}
+
+impl Classpath {
+    /// Adds a class, or records it as a duplicate if a class of the same name was found before,
+    /// as that one is loaded.
+    fn insert(&mut self, name: String, class: Class, digest: u64, origin: ClassOrigin) {
+        let Some(kept) = self.classes.get(&name) else {
+            self.origins.insert(name.clone(), origin);
+            self.digests.insert(name.clone(), digest);
+            self.classes.insert(name, class);
+            return;
+        };
+        let methods_differ = kept.get_methods().ok() != class.get_methods().ok();
+        let bytes_differ = self.digests.get(&name) != Some(&digest);
+        let duplicate = self
+            .duplicates
+            .entry(name.clone())
+            .or_insert_with(|| DuplicateClass {
+                archives: self
+                    .origins
+                    .get(&name)
+                    .map(|origin| origin.archive.clone())
+                    .into_iter()
+                    .collect(),
+                methods_differ: false,
+                bytes_differ: false,
+            });
+        duplicate.archives.push(origin.archive);
+        duplicate.methods_differ |= methods_differ;
+        duplicate.bytes_differ |= bytes_differ;
+    }
+
+    /// Adds the classes of `other` which are not yet known and records the others as duplicates.
+    fn merge(&mut self, mut other: Classpath) {
+        for (name, class) in other.classes {
+            let origin = other.origins.remove(&name).unwrap_or_default();
+            let digest = other.digests.remove(&name).unwrap_or_default();
+            self.insert(name, class, digest, origin);
+        }
+        for (name, other_duplicate) in other.duplicates {
+            match self.duplicates.get_mut(&name) {
+                //the first archive of the other duplicate was added when merging its class
+                Some(duplicate) => {
+                    duplicate
+                        .archives
+                        .extend(other_duplicate.archives.into_iter().skip(1));
+                    duplicate.methods_differ |= other_duplicate.methods_differ;
+                    duplicate.bytes_differ |= other_duplicate.bytes_differ;
+                }
+                None => {
+                    self.duplicates.insert(name, other_duplicate);
+                }
+            }
+        }
+        self.failures.extend(other.failures);
+        self.releases.extend(other.releases);
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions {
+    pub parallel: bool,
+    ///Skip JARs and classes that cannot be read instead of aborting.
+    pub keep_going: bool,
+    ///The Java release used to pick classes from multi-release JARs. Only the
+    ///base classes are used if not set.
+    pub release: Option<u16>,
+    ///Add the JARs listed in the `Class-Path` manifest attribute of each JAR.
+    pub manifest_classpath: bool,
+}
+
+const VERSIONS_PREFIX: &str = "META-INF/versions/";
+//versioned entries for lower releases are ignored by the JVM
+const FIRST_VERSIONED_RELEASE: u16 = 9;
+
+/// How the classpath of an archive is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    Jar,
+    //Spring Boot fat JAR
+    Boot,
+    War,
+    Ear,
+}
+
+impl Layout {
+    fn detect(path: &Path, names: &[&str]) -> Self {
+        let has_prefix = |prefix: &str| names.iter().any(|n| n.starts_with(prefix));
+        if has_prefix("BOOT-INF/") {
+            Layout::Boot
+        } else if has_prefix("WEB-INF/") {
+            Layout::War
+        } else if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("ear"))
+            || names.contains(&"META-INF/application.xml")
+        {
+            Layout::Ear
+        } else {
+            Layout::Jar
+        }
+    }
+
+    /// The path of the class an entry contains, if it is on the classpath.
+    fn class_path<'a>(&self, name: &'a str) -> Option<&'a str> {
+        if !name.ends_with(".class") {
+            return None;
+        }
+        match self {
+            Layout::Jar => Some(name),
+            Layout::Boot => match name.strip_prefix("BOOT-INF/") {
+                Some(name) => name.strip_prefix("classes/"),
+                //the launcher
+                None => Some(name),
+            },
+            Layout::War => name.strip_prefix("WEB-INF/classes/"),
+            Layout::Ear => None,
+        }
+    }
+
+    /// Whether an entry is a nested archive on the classpath.
+    fn is_nested_archive(&self, name: &str) -> bool {
+        let in_dir = |dir: &str, extensions: &[&str]| {
+            name.strip_prefix(dir).is_some_and(|file| {
+                !file.contains('/') && extensions.iter().any(|e| file.ends_with(e))
+            })
+        };
+        match self {
+            Layout::Jar => false,
+            Layout::Boot => in_dir("BOOT-INF/lib/", &[".jar"]),
+            Layout::War => in_dir("WEB-INF/lib/", &[".jar"]),
+            //modules and the default library directory
+            Layout::Ear => in_dir("", &[".jar", ".war"]) || in_dir("lib/", &[".jar"]),
+        }
+    }
+}
+
+fn open_archive(path: &Path) -> Result<File> {
+    Ok(File::options()
         .read(true)
         .write(false)
         .create_new(false)
-        .open(path)?;
-    let mut archive = ZipArchive::new(file)?;
-    let mut classes = HashMap::default();
+        .open(path)?)
+}
+
+/// Passes the file entries accepted by `filter` to `consumer`. Entries whose names are absolute
+/// or leave the archive with `..` are skipped unless `unsafe_names` is set.
+fn read_zip_entries<R, F, C>(
+    reader: R,
+    unsafe_names: bool,
+    filter: F,
+    mut consumer: C,
+) -> Result<()>
+where
+    R: Read + Seek,
+    F: Fn(&str) -> bool,
+    C: FnMut(&str, Result<Vec<u8>>) -> Result<()>,
+{
+    let mut archive = ZipArchive::new(reader)?;
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
-        if let Some(path) = file.enclosed_name() {
-            if let Some(ext) = path.extension() {
-                if ext.eq("class") {
-                    let mut file_inmem: Vec<u8> = vec![];
-                    if file.read_to_end(&mut file_inmem).is_err() {
-                        continue;
-                    }
-                    let class_parsed = Class::from(&mut Cursor::new(file_inmem));
-                    let ConstPoolEntry::Class { name_index } =
-                        &class_parsed.const_pool[&class_parsed.this_class_idx]
-                    else {
-                        continue;
-                    };
-                    let Ok(class_name) = class_parsed.get_utf8(name_index) else {
-                        continue;
-                    };
-                    classes.insert(class_name.to_owned(), class_parsed);
+        if file.is_dir() || (!unsafe_names && file.enclosed_name().is_none()) {
+            continue;
+        }
+        let name = file.name().to_owned();
+        if !filter(&name) {
+            continue;
+        }
+        let mut file_inmem: Vec<u8> = vec![];
+        let data = file
+            .read_to_end(&mut file_inmem)
+            .map(|_| file_inmem)
+            .map_err(error::Error::from);
+        consumer(&name, data)?;
+    }
+    Ok(())
+}
+
+/// Parses a class file and returns its name, the class and a digest of the bytes.
+fn read_class(data: Vec<u8>) -> Result<(String, Class, u64)> {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    let digest = hasher.finish();
+    let class = Class::from(&mut Cursor::new(data))?;
+    let name = class.get_name().map_err(FormatError)?.to_owned();
+    Ok((name, class, digest))
+}
+
+/// Splits a path below `META-INF/versions/` into its release and the path of the class.
+fn versioned_path(name: &str) -> Option<(u16, &str)> {
+    let (version, path) = name.strip_prefix(VERSIONS_PREFIX)?.split_once('/')?;
+    Some((version.parse().ok()?, path))
+}
+
+fn is_multi_release(manifest: Vec<u8>) -> Result<bool> {
+    let manifest = Manifest::parse(manifest).map_err(FormatError)?;
+    Ok(manifest
+        .main_section()
+        .get("Multi-Release")
+        .is_some_and(|v| v.eq_ignore_ascii_case("true")))
+}
+
+/// Picks the class entries the JVM of the given release would load. For every class of a
+/// multi-release JAR this is the variant with the highest version not above the release.
+/// The versioned entries of other JARs are never loaded. Further unversioned entries of the
+/// same class, like a Spring Boot launcher class also kept in `BOOT-INF/classes/`, come last
+/// so they are recorded as duplicates.
+fn select_release<T>(
+    entries: Vec<(String, T)>,
+    multi_release: bool,
+    release: Option<u16>,
+    releases: &mut BTreeSet<u16>,
+) -> Vec<(String, T)> {
+    let mut selected: HashMap<String, (u16, usize)> = HashMap::default();
+    let mut unversioned: HashSet<&str> = HashSet::default();
+    let mut shadowed = vec![];
+    for (idx, (name, _)) in entries.iter().enumerate() {
+        let (version, path) = match versioned_path(name) {
+            Some(_) if !multi_release => continue,
+            Some((version, _)) if version < FIRST_VERSIONED_RELEASE => continue,
+            Some((version, path)) => {
+                releases.insert(version);
+                if release.is_none_or(|r| version > r) {
+                    continue;
                 }
+                (version, path)
+            }
+            None if name.starts_with(VERSIONS_PREFIX) => continue,
+            None => (0, name.as_str()),
+        };
+        if version == 0 && !unversioned.insert(path) {
+            shadowed.push(idx);
+            continue;
+        }
+        match selected.get_mut(path) {
+            Some(current) if current.0 >= version => {}
+            Some(current) => *current = (version, idx),
+            None => {
+                selected.insert(path.to_owned(), (version, idx));
             }
         }
     }
-    Ok(classes)
+    let mut keep = vec![false; entries.len()];
+    for (_, idx) in selected.values() {
+        keep[*idx] = true;
+    }
+    let mut entries: Vec<Option<(String, T)>> = entries.into_iter().map(Some).collect();
+    let mut result: Vec<(String, T)> = entries
+        .iter_mut()
+        .zip(keep)
+        .filter_map(|(entry, keep)| if keep { entry.take() } else { None })
+        .collect();
+    result.extend(shadowed.into_iter().filter_map(|idx| entries[idx].take()));
+    result
 }
 
-pub fn parse_classpath(cp: &str, parallel: bool) -> Result<HashMap<String, Class>> {
-    info!("Processing class path");
-    let split = cp.split(';');
-    let expanded = split
-        .map(|el| shellexpand::full(el).unwrap_or_else(|_| panic!("Failed to expand path {el}")));
-    let globbed = expanded
-        .clone()
-        .filter_map(|el| {
-            if el.contains('*') {
-                Some(glob::glob(el.as_ref()).unwrap().map(|p| p.unwrap()))
+/// Reads the classes of an archive. If `detect_layout` is set, the classes and nested
+/// archives are taken from the places a Spring Boot fat JAR, WAR or EAR keeps them.
+fn read_archive<R: Read + Seek>(
+    reader: R,
+    path: &Path,
+    detect_layout: bool,
+    options: &ParseOptions,
+) -> Result<Classpath> {
+    debug!("Processing file {}...", path.display());
+    let mut result = Classpath::default();
+    let mut manifest = None;
+    let mut entries = vec![];
+    read_zip_entries(
+        reader,
+        false,
+        |name| {
+            name == MANIFEST_PATH
+                || name.ends_with(".class")
+                || name.ends_with(".jar")
+                || name.ends_with(".war")
+        },
+        |name, data| {
+            if name == MANIFEST_PATH {
+                manifest = Some(data.map_err(|e| e.in_archive(path, Some(name)))?);
             } else {
-                None
+                entries.push((name.to_owned(), data));
             }
-        })
-        .flatten();
-    let concrete = expanded.filter_map(|el| {
-        if !el.contains('*') {
-            let mut p = PathBuf::new();
-            p.push(el.as_ref());
-            Some(p)
-        } else {
-            None
-        }
-    });
-    let chained: Vec<PathBuf> = globbed.chain(concrete).collect();
-    debug!("{} JAR files found.", chained.len());
-    let result = if parallel {
-        chained
-            .par_iter()
-            .map(|pb| read_zip_archive(pb.as_path()).unwrap())
-            .reduce(HashMap::default, |a, mut b| {
-                a.into_iter().for_each(|(k, v)| {
-                    b.insert(k, v);
-                });
-                b
-            })
+            Ok(())
+        },
+    )
+    .map_err(|e| e.in_archive(path, None))?;
+
+    let layout = if detect_layout {
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        Layout::detect(path, &names)
     } else {
-        chained
-            .iter()
-            .map(|pb| read_zip_archive(pb.as_path()).unwrap())
-            .reduce(|a, mut b| {
-                a.into_iter().for_each(|(k, v)| {
-                    b.insert(k, v);
-                });
-                b
+        Layout::Jar
+    };
+    if layout != Layout::Jar {
+        trace!("{} has the layout {:?}", path.display(), layout);
+    }
+    let mut classes = vec![];
+    let mut nested = vec![];
+    for (name, data) in entries {
+        if let Some(class_path) = layout.class_path(&name) {
+            classes.push((class_path.to_owned(), (name, data)));
+        } else if layout.is_nested_archive(&name) {
+            nested.push((name, data));
+        }
+    }
+
+    let multi_release = match manifest {
+        Some(manifest) => {
+            is_multi_release(manifest).map_err(|e| e.in_archive(path, Some(MANIFEST_PATH)))?
+        }
+        None => false,
+    };
+    if multi_release {
+        trace!("{} is a multi-release JAR", path.display());
+    }
+    let classes = select_release(
+        classes,
+        multi_release,
+        options.release,
+        &mut result.releases,
+    );
+
+    for (_, (name, data)) in classes {
+        match data.and_then(read_class) {
+            Ok((class_name, class, digest)) => {
+                let origin = ClassOrigin {
+                    archive: path.to_owned(),
+                    entry: name,
+                };
+                result.insert(class_name, class, digest, origin);
+            }
+            Err(e) => skip_or_fail(&mut result, e.in_archive(path, Some(&name)), options)?,
+        }
+    }
+
+    for (name, data) in nested {
+        let nested_path = PathBuf::from(format!("{}!/{}", path.display(), name));
+        let archive = data
+            .map_err(|e| e.in_archive(path, Some(&name)))
+            .and_then(|data| {
+                read_archive(
+                    Cursor::new(data),
+                    &nested_path,
+                    layout == Layout::Ear,
+                    options,
+                )
             })
-            .unwrap_or(HashMap::default())
+            .map_err(|e| e.in_archive(&nested_path, None));
+        match archive {
+            Ok(archive) => result.merge(archive),
+            Err(e) => skip_or_fail(&mut result, e, options)?,
+        }
+    }
+    Ok(result)
+}
+
+/// Records an unreadable class or archive when keeping going, fails otherwise.
+fn skip_or_fail(result: &mut Classpath, e: error::Error, options: &ParseOptions) -> Result<()> {
+    if !options.keep_going {
+        return Err(e);
+    }
+    warn!("Skipping {}", e);
+    if let error::Error::ArchiveError(e) = e {
+        result.failures.push(e);
+    }
+    Ok(())
+}
+
+/// Collects the `.class` files below `dir`, sorted by path.
+fn find_class_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            find_class_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "class") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a single class file. If `root` is given, the class name must match the path
+/// of the file below it, like the JVM expects for class directories.
+fn read_class_file(path: &Path, root: Option<&Path>) -> Result<(String, Class, u64)> {
+    let (name, class, digest) = read_class(std::fs::read(path)?)?;
+    if let Some(root) = root {
+        let relative = path.strip_prefix(root).unwrap_or(path).with_extension("");
+        if entry_name(&relative) != name {
+            return Err(FormatError(format!(
+                "Class {name} does not match its location {}",
+                relative.display()
+            ))
+            .into());
+        }
+    }
+    Ok((name, class, digest))
+}
+
+/// The path of a file below a class directory, separated by slashes like a JAR entry.
+fn entry_name(relative: &Path) -> String {
+    let components: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    components.join("/")
+}
+
+/// Reads the classes of an exploded class directory.
+fn read_directory(path: &Path, options: &ParseOptions) -> Result<Classpath> {
+    debug!("Processing directory {}...", path.display());
+    let mut result = Classpath::default();
+    let mut files = vec![];
+    find_class_files(path, &mut files).map_err(|e| e.in_archive(path, None))?;
+    for file in files {
+        //classes of other releases, the directory is not a multi-release JAR
+        if file
+            .strip_prefix(path)
+            .is_ok_and(|f| f.starts_with(VERSIONS_PREFIX))
+        {
+            continue;
+        }
+        match read_class_file(&file, Some(path)) {
+            Ok((name, class, digest)) => {
+                let origin = ClassOrigin {
+                    archive: path.to_owned(),
+                    entry: entry_name(file.strip_prefix(path).unwrap_or(&file)),
+                };
+                result.insert(name, class, digest, origin);
+            }
+            Err(e) => skip_or_fail(&mut result, e.in_archive(&file, None), options)?,
+        }
+    }
+    Ok(result)
+}
+
+/// Reads a class path element, which may be an archive, a class directory or a single class.
+fn read_classpath_element(path: &Path, options: &ParseOptions) -> Result<Classpath> {
+    if path.is_dir() {
+        return read_directory(path, options);
+    }
+    if path.extension().is_some_and(|e| e == "class") {
+        debug!("Processing class file {}...", path.display());
+        let (name, class, digest) =
+            read_class_file(path, None).map_err(|e| e.in_archive(path, None))?;
+        let origin = ClassOrigin {
+            archive: path.to_owned(),
+            entry: path
+                .file_name()
+                .map(|f| f.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        let mut result = Classpath::default();
+        result.insert(name, class, digest, origin);
+        return Ok(result);
+    }
+    open_archive(path)
+        .and_then(|file| read_archive(file, path, true, options))
+        .map_err(|e| e.in_archive(path, None))
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolves a relative URL of a `Class-Path` attribute against the directory of the JAR.
+fn resolve_reference(jar: &Path, reference: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(reference)?;
+    if let Some(path) = decoded.strip_prefix("file:") {
+        return Some(PathBuf::from(path));
+    }
+    match decoded.split_once(':') {
+        //other URL schemes, a single letter is a Windows drive
+        Some((scheme, _)) if scheme.len() > 1 && !scheme.contains('/') => None,
+        _ => Some(jar.parent().unwrap_or(Path::new("")).join(decoded)),
+    }
+}
+
+/// The raw `Class-Path` entries of a JAR's manifest.
+fn manifest_classpath(path: &Path) -> Result<Vec<String>> {
+    let mut manifest = None;
+    read_zip_entries(
+        open_archive(path)?,
+        false,
+        |name| name == MANIFEST_PATH,
+        |_, data| {
+            manifest = Some(data?);
+            Ok(())
+        },
+    )?;
+    let Some(manifest) = manifest else {
+        return Ok(vec![]);
     };
+    let manifest = Manifest::parse(manifest).map_err(FormatError)?;
+    Ok(manifest
+        .main_section()
+        .get("Class-Path")
+        .map(|cp| cp.split_whitespace().map(str::to_owned).collect())
+        .unwrap_or_default())
+}
+
+/// Adds the JARs referenced by `Class-Path` manifest attributes after the JAR referencing
+/// them, the way the JVM's class loader searches them. Every JAR is only added once.
+fn follow_manifest_classpath(
+    elements: Vec<PathBuf>,
+    missing: &mut Vec<MissingReference>,
+) -> Vec<PathBuf> {
+    let mut result = vec![];
+    let mut visited: HashSet<PathBuf> = HashSet::default();
+    let mut stack: Vec<PathBuf> = elements.into_iter().rev().collect();
+    while let Some(path) = stack.pop() {
+        let key = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if !visited.insert(key) {
+            trace!("{} already on the class path", path.display());
+            continue;
+        }
+        if path.is_file() && path.extension().is_none_or(|e| e != "class") {
+            //unreadable JARs are reported when reading their classes
+            let references = manifest_classpath(&path).unwrap_or_else(|e| {
+                debug!("Cannot read manifest of {}: {}", path.display(), e);
+                vec![]
+            });
+            for reference in references.iter().rev() {
+                match resolve_reference(&path, reference) {
+                    Some(resolved) if resolved.exists() => stack.push(resolved),
+                    Some(_) => missing.push(MissingReference {
+                        jar: path.clone(),
+                        reference: reference.clone(),
+                    }),
+                    None => warn!(
+                        "Ignoring unsupported Class-Path entry {} of {}",
+                        reference,
+                        path.display()
+                    ),
+                }
+            }
+        }
+        result.push(path);
+    }
+    result
+}
+
+/// Reads every file entry of a JAR archive in the order they are stored, including entries
+/// with absolute or `..` names, as these must be covered by a signature, too.
+pub fn read_jar_entries(path: &Path) -> Result<Vec<(String, Vec<u8>)>> {
+    debug!("Reading entries of {}...", path.display());
+    let mut entries = vec![];
+    read_zip_entries(
+        open_archive(path).map_err(|e| e.in_archive(path, None))?,
+        true,
+        |_| true,
+        |name, data| {
+            let data = data.map_err(|e| e.in_archive(path, Some(name)))?;
+            entries.push((name.to_owned(), data));
+            Ok(())
+        },
+    )
+    .map_err(|e| e.in_archive(path, None))?;
+    Ok(entries)
+}
+
+/// Expands a semicolon-separated class path into the paths it denotes.
+pub fn expand_classpath(cp: &str) -> Result<Vec<PathBuf>> {
+    let mut result = vec![];
+    for el in cp.split(';') {
+        let expanded = shellexpand::full(el)?;
+        if expanded.contains('*') {
+            for path in glob::glob(expanded.as_ref())? {
+                result.push(path?);
+            }
+        } else {
+            result.push(PathBuf::from(expanded.as_ref()));
+        }
+    }
+    Ok(result)
+}
+
+/// Reads all classes of the given class path of JARs, class directories and class files.
+/// Like the JVM, the first element containing a class wins, the others are listed in
+/// [`Classpath::duplicates`].
+///
+/// If [`ParseOptions::keep_going`] is set, JARs and classes that cannot be read are
+/// collected in [`Classpath::failures`] instead of aborting.
+pub fn parse_classpath(cp: &str, options: &ParseOptions) -> Result<Classpath> {
+    info!("Processing class path");
+    let mut chained = expand_classpath(cp)?;
+    let mut missing_references = vec![];
+    if options.manifest_classpath {
+        chained = follow_manifest_classpath(chained, &mut missing_references);
+    }
+    debug!("{} class path elements found.", chained.len());
+    let read = |pb: &PathBuf| read_classpath_element(pb, options);
+    let archives: Vec<Result<Classpath>> = if options.parallel {
+        chained.par_iter().map(read).collect()
+    } else {
+        chained.iter().map(read).collect()
+    };
+
+    let mut result = Classpath {
+        missing_references,
+        ..Default::default()
+    };
+    for archive in archives {
+        match archive {
+            Ok(archive) => result.merge(archive),
+            Err(error::Error::ArchiveError(e)) if options.keep_going => {
+                warn!("Skipping {}", e);
+                result.failures.push(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
 
-    info!("Finished. {} classes found.", result.len());
+    info!(
+        "Finished. {} classes found, {} failures.",
+        result.classes.len(),
+        result.failures.len()
+    );
     Ok(result)
 }
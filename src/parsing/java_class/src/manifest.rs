@@ -0,0 +1,169 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::ops::Range;
+
+use ahash::AHashMap;
+use log::trace;
+
+type HashMap<K, V> = AHashMap<K, V>;
+
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// A parsed JAR manifest (or signature file, which shares the format).
+///
+/// Besides the attributes, the raw bytes of every section are kept, since
+/// signature files contain digests over exactly those bytes.
+#[derive(Debug)]
+pub struct Manifest {
+    raw: Vec<u8>,
+    main: Section,
+    sections: Vec<Section>,
+    section_indexes: HashMap<String, usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Section {
+    pub name: Option<String>,
+    attributes: Vec<(String, String)>,
+    //a section may be split over multiple parts with the same name
+    ranges: Vec<Range<usize>>,
+}
+
+impl Section {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn attributes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.attributes
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+impl Manifest {
+    pub fn parse(raw: Vec<u8>) -> Result<Self, String> {
+        let mut main = None;
+        let mut sections: Vec<Section> = vec![];
+        let mut section_indexes = HashMap::default();
+        let mut pos = 0;
+        while pos < raw.len() {
+            let (section, next) = parse_section(&raw, pos)?;
+            pos = next;
+            let Some(section) = section else {
+                continue;
+            };
+            if main.is_none() {
+                main = Some(section);
+                continue;
+            }
+            let Some(name) = section.name.clone() else {
+                return Err(format!(
+                    "Section at byte {} has no Name attribute",
+                    section.ranges[0].start
+                ));
+            };
+            trace!("Manifest section {}", name);
+            match section_indexes.get(&name) {
+                Some(&idx) => {
+                    let existing: &mut Section = &mut sections[idx];
+                    existing.attributes.extend(section.attributes);
+                    existing.ranges.extend(section.ranges);
+                }
+                None => {
+                    section_indexes.insert(name, sections.len());
+                    sections.push(section);
+                }
+            }
+        }
+        Ok(Manifest {
+            raw,
+            main: main.unwrap_or_default(),
+            sections,
+            section_indexes,
+        })
+    }
+
+    pub fn main_section(&self) -> &Section {
+        &self.main
+    }
+
+    pub fn sections(&self) -> &[Section] {
+        &self.sections
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.section_indexes
+            .get(name)
+            .map(|&idx| &self.sections[idx])
+    }
+
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+
+    /// The raw bytes of a section, including the blank line terminating each part.
+    pub fn raw_parts<'a>(&'a self, section: &'a Section) -> impl Iterator<Item = &'a [u8]> {
+        section.ranges.iter().map(|r| &self.raw[r.clone()])
+    }
+}
+
+/// Returns the end of the line starting at `pos` and the start of the next line.
+fn line_end(raw: &[u8], pos: usize) -> (usize, usize) {
+    let mut i = pos;
+    while i < raw.len() {
+        match raw[i] {
+            b'\n' => return (i, i + 1),
+            b'\r' if raw.get(i + 1) == Some(&b'\n') => return (i, i + 2),
+            b'\r' => return (i, i + 1),
+            _ => i += 1,
+        }
+    }
+    (i, i)
+}
+
+fn parse_section(raw: &[u8], start: usize) -> Result<(Option<Section>, usize), String> {
+    let mut lines: Vec<Vec<u8>> = vec![];
+    let mut pos = start;
+    while pos < raw.len() {
+        let (end, next) = line_end(raw, pos);
+        let line = &raw[pos..end];
+        pos = next;
+        if line.is_empty() {
+            break;
+        }
+        match (line[0], lines.last_mut()) {
+            (b' ', Some(last)) => last.extend_from_slice(&line[1..]),
+            (b' ', None) => return Err(format!("Continuation without header at byte {start}")),
+            _ => lines.push(line.to_vec()),
+        }
+    }
+    if lines.is_empty() {
+        return Ok((None, pos));
+    }
+    let mut section = Section {
+        name: None,
+        attributes: Vec::with_capacity(lines.len()),
+        ranges: std::iter::once(start..pos).collect(),
+    };
+    for line in lines {
+        let line = String::from_utf8_lossy(&line);
+        let Some((key, value)) = line.split_once(": ") else {
+            return Err(format!("Malformed manifest header '{line}'"));
+        };
+        if section.name.is_none() && key.eq_ignore_ascii_case("Name") {
+            section.name = Some(value.to_owned());
+        }
+        section.attributes.push((key.to_owned(), value.to_owned()));
+    }
+    Ok((Some(section), pos))
+}
@@ -0,0 +1,364 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::path::Path;
+
+use java_class::ClassOrigin;
+use reference_checker::{DependencyKind, DependencyStatus, UnmetDependency};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::{Checked, HashMap};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const VERSION: &str = "2.1.0";
+/// Key of the partial fingerprint, versioned in case its computation ever changes.
+const FINGERPRINT: &str = "jarVerifier/v1";
+
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    MissingClass,
+    MissingMethod,
+    MissingField,
+    KindMismatch,
+    StaticMismatch,
+    IllegalAccess,
+    UnimplementedMethod,
+    DuplicateClass,
+    MissingClasspathEntry,
+}
+
+const RULES: [Rule; 9] = [
+    Rule::MissingClass,
+    Rule::MissingMethod,
+    Rule::MissingField,
+    Rule::KindMismatch,
+    Rule::StaticMismatch,
+    Rule::IllegalAccess,
+    Rule::UnimplementedMethod,
+    Rule::DuplicateClass,
+    Rule::MissingClasspathEntry,
+];
+
+impl Rule {
+    fn id(self) -> &'static str {
+        match self {
+            Rule::MissingClass => "missing-class",
+            Rule::MissingMethod => "missing-method",
+            Rule::MissingField => "missing-field",
+            Rule::KindMismatch => "class-interface-mismatch",
+            Rule::StaticMismatch => "static-mismatch",
+            Rule::IllegalAccess => "illegal-access",
+            Rule::UnimplementedMethod => "unimplemented-method",
+            Rule::DuplicateClass => "duplicate-class",
+            Rule::MissingClasspathEntry => "missing-classpath-entry",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Rule::MissingClass => "A referenced class is not on the class path",
+            Rule::MissingMethod => "A referenced method does not exist in its class",
+            Rule::MissingField => "A referenced field does not exist in its class",
+            Rule::KindMismatch => "A class is used as an interface or vice versa",
+            Rule::StaticMismatch => {
+                "An instance method is invoked as a static method or vice versa"
+            }
+            Rule::IllegalAccess => "A class or member is not accessible to the class using it",
+            Rule::UnimplementedMethod => {
+                "A concrete class inherits an abstract method without implementing it"
+            }
+            Rule::DuplicateClass => "A class is found in more than one place on the class path",
+            Rule::MissingClasspathEntry => "A Class-Path manifest entry does not exist",
+        }
+    }
+
+    fn level(self) -> &'static str {
+        match self {
+            Rule::DuplicateClass | Rule::MissingClasspathEntry => "warning",
+            _ => "error",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    fn descriptor(self) -> Value {
+        json!({
+            "id": self.id(),
+            "shortDescription": {"text": self.description()},
+            "defaultConfiguration": {"level": self.level()},
+        })
+    }
+}
+
+/// The artifacts referenced by the results. Class entries are nested in their archive or
+/// directory.
+#[derive(Default)]
+struct Artifacts {
+    artifacts: Vec<Value>,
+    indices: HashMap<(String, Option<String>), usize>,
+}
+
+impl Artifacts {
+    fn index(&mut self, archive: &Path, entry: Option<&str>) -> usize {
+        let key = (uri(archive), entry.map(str::to_owned));
+        if let Some(index) = self.indices.get(&key) {
+            return *index;
+        }
+        let artifact = match entry {
+            Some(entry) => {
+                let parent = self.index(archive, None);
+                json!({"location": {"uri": encode(entry)}, "parentIndex": parent})
+            }
+            None => json!({"location": {"uri": key.0}}),
+        };
+        let index = self.artifacts.len();
+        self.artifacts.push(artifact);
+        self.indices.insert(key, index);
+        index
+    }
+
+    fn location(&mut self, archive: &Path, entry: Option<&str>) -> Value {
+        let index = self.index(archive, entry);
+        self.artifacts[index]["location"]
+            .as_object()
+            .map(|location| {
+                let mut location = location.clone();
+                location.insert("index".to_owned(), json!(index));
+                Value::Object(location)
+            })
+            .expect("Artifact without location")
+    }
+}
+
+/// A relative URI reference for a path, with forward slashes.
+fn uri(path: &Path) -> String {
+    encode(&path.to_string_lossy().replace('\\', "/"))
+}
+
+fn encode(path: &str) -> String {
+    let mut result = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                result.push(b as char)
+            }
+            b':' | b'!' | b'$' | b'+' | b'@' => result.push(b as char),
+            _ => result.push_str(&format!("%{b:02X}")),
+        }
+    }
+    result
+}
+
+/// Hashes the parts identifying a result. Paths are left out, so that moving or renaming a JAR
+/// keeps the fingerprint.
+fn fingerprint(rule: Rule, parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(rule.id());
+    for part in parts {
+        hasher.update([0]);
+        hasher.update(part);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn result(rule: Rule, message: String, location: Value, fingerprint: String) -> Value {
+    json!({
+        "ruleId": rule.id(),
+        "ruleIndex": rule.index(),
+        "level": rule.level(),
+        "message": {"text": message},
+        "locations": [location],
+        "partialFingerprints": {FINGERPRINT: fingerprint},
+    })
+}
+
+fn class_location(artifacts: &mut Artifacts, class: &str, origin: Option<&ClassOrigin>) -> Value {
+    let mut location = json!({
+        "logicalLocations": [{"fullyQualifiedName": class, "kind": "type"}],
+    });
+    if let Some(origin) = origin {
+        location["physicalLocation"] = json!({
+            "artifactLocation": artifacts.location(&origin.archive, Some(&origin.entry)),
+        });
+    }
+    location
+}
+
+fn dependency_results(
+    consumer: &str,
+    dependency: &UnmetDependency,
+    location: &Value,
+    results: &mut Vec<Value>,
+) {
+    let name = dependency.name;
+    let mut add = |rule: Rule, message: String, member: &str| {
+        let fingerprint = fingerprint(rule, &[consumer, name, member]);
+        results.push(result(rule, message, location.clone(), fingerprint));
+    };
+    match dependency.status {
+        DependencyStatus::Missing => add(
+            Rule::MissingClass,
+            format!("{consumer} requires the missing class {name}"),
+            "",
+        ),
+        DependencyStatus::KindMismatch => {
+            let message = match dependency.kind {
+                DependencyKind::Class => {
+                    format!("{consumer} uses {name} as a class, but it is an interface")
+                }
+                DependencyKind::Interface => {
+                    format!("{consumer} uses {name} as an interface, but it is a class")
+                }
+            };
+            add(Rule::KindMismatch, message, "")
+        }
+        DependencyStatus::Inaccessible => add(
+            Rule::IllegalAccess,
+            format!("{consumer} requires {name}, which is neither public nor in its package"),
+            "",
+        ),
+        DependencyStatus::ApiMismatch => {
+            for method in &dependency.methods {
+                add(
+                    Rule::MissingMethod,
+                    format!("{consumer} requires the missing method {name}.{method}"),
+                    method,
+                );
+            }
+            for field in &dependency.fields {
+                add(
+                    Rule::MissingField,
+                    format!("{consumer} requires the missing field {name}.{field}"),
+                    field,
+                );
+            }
+            for mismatch in &dependency.static_mismatches {
+                let method = mismatch.method;
+                let message = if mismatch.is_static {
+                    format!("{consumer} requires {name}.{method} to be an instance method")
+                } else {
+                    format!("{consumer} requires {name}.{method} to be static")
+                };
+                add(Rule::StaticMismatch, message, method);
+            }
+            for inaccessible in &dependency.inaccessible_members {
+                let member = inaccessible.member;
+                add(
+                    Rule::IllegalAccess,
+                    format!(
+                        "{consumer} requires {name}.{member}, which is {}",
+                        inaccessible.access
+                    ),
+                    member,
+                );
+            }
+            for method in &dependency.unimplemented_methods {
+                add(
+                    Rule::UnimplementedMethod,
+                    format!("{consumer} does not implement the abstract method {name}.{method}"),
+                    method,
+                );
+            }
+        }
+    }
+}
+
+/// Converts the result of one check to a SARIF run.
+pub fn report(checked: &Checked) -> Value {
+    let classpath = checked.classpath;
+    let mut artifacts = Artifacts::default();
+    let mut results = Vec::new();
+    for requirements in &checked.requirements {
+        let consumer = requirements.name();
+        let origin = classpath.origins.get(consumer);
+        let location = class_location(&mut artifacts, consumer, origin);
+        for dependency in requirements.dependencies() {
+            dependency_results(consumer, &dependency, &location, &mut results);
+        }
+    }
+    //with a baseline, only the new findings are left
+    if checked.baseline.is_some() {
+        for result in &mut results {
+            result["baselineState"] = json!("new");
+        }
+    }
+    for (name, duplicate) in &classpath.duplicates {
+        let location = class_location(&mut artifacts, name, classpath.origins.get(name));
+        let archives: Vec<String> = duplicate
+            .archives
+            .iter()
+            .map(|a| a.display().to_string())
+            .collect();
+        let message = format!("{name} is found in {}", archives.join(", "));
+        let fingerprint = fingerprint(Rule::DuplicateClass, &[name]);
+        results.push(result(Rule::DuplicateClass, message, location, fingerprint));
+    }
+    for missing in &classpath.missing_references {
+        let location = json!({
+            "physicalLocation": {
+                "artifactLocation": artifacts.location(&missing.jar, Some("META-INF/MANIFEST.MF")),
+            },
+        });
+        let message = format!("Class-Path entry {} does not exist", missing.reference);
+        let fingerprint = fingerprint(Rule::MissingClasspathEntry, &[&missing.reference]);
+        results.push(result(
+            Rule::MissingClasspathEntry,
+            message,
+            location,
+            fingerprint,
+        ));
+    }
+    let notifications: Vec<Value> = classpath
+        .failures
+        .iter()
+        .map(|failure| {
+            json!({
+                "level": "error",
+                "message": {"text": failure.to_string()},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation":
+                            artifacts.location(&failure.path, failure.entry.as_deref()),
+                    },
+                }],
+            })
+        })
+        .collect();
+    json!({
+        "tool": {
+            "driver": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": RULES.iter().map(|r| r.descriptor()).collect::<Vec<_>>(),
+            },
+        },
+        "invocations": [{
+            "executionSuccessful": notifications.is_empty(),
+            "toolExecutionNotifications": notifications,
+        }],
+        "artifacts": artifacts.artifacts,
+        "results": results,
+        "properties": {"release": checked.release},
+    })
+}
+
+pub fn format(runs: impl IntoIterator<Item = Value>) -> String {
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": VERSION,
+        "runs": runs.into_iter().collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&log).expect("Failed to serialize report")
+}
@@ -0,0 +1,229 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+use std::{
+    fmt::Display,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{info, warn};
+use reference_checker::ClassRequirements;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, SuppressionError};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuppressionFile {
+    suppressions: Vec<Suppression>,
+}
+
+/// An accepted finding. Every given pattern must match, an entry without `member` matches
+/// missing classes and class/interface mismatches as well.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    //pattern of the class with the unmet dependency
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consumer: Option<String>,
+    //pattern of the required class
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependency: Option<String>,
+    //pattern of the missing method or field, as in the report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    member: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    //YYYY-MM-DD, the first day the entry no longer applies
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+}
+
+impl Suppression {
+    fn matches(&self, consumer: &str, dependency: &str, member: Option<&str>) -> bool {
+        let class = |pattern: &Option<String>, name: &str| {
+            pattern
+                .as_ref()
+                .is_none_or(|p| glob(&p.replace('.', "/"), name, true))
+        };
+        class(&self.consumer, consumer)
+            && class(&self.dependency, dependency)
+            && match (&self.member, member) {
+                (None, _) => true,
+                (Some(pattern), Some(member)) => glob(pattern, member, false),
+                (Some(_), None) => false,
+            }
+    }
+
+    fn is_expired(&self, today: &str) -> bool {
+        self.expires
+            .as_deref()
+            .is_some_and(|expires| expires <= today)
+    }
+}
+
+impl Display for Suppression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        for (key, value) in [
+            ("consumer", &self.consumer),
+            ("dependency", &self.dependency),
+            ("member", &self.member),
+            ("expires", &self.expires),
+        ] {
+            if let Some(value) = value {
+                parts.push(format!("{key} {value}"));
+            }
+        }
+        write!(f, "{}", parts.join(", "))?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({reason})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Matches `text` against a pattern, where `**` matches anything and `*` anything but a `/`
+/// for classes, or anything at all for members.
+fn glob(pattern: &str, text: &str, class: bool) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**") {
+        return text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain([text.len()])
+            .any(|i| glob(rest, &text[i..], class));
+    }
+    match pattern.chars().next() {
+        None => text.is_empty(),
+        Some('*') => {
+            let rest = &pattern[1..];
+            for (i, c) in text.char_indices() {
+                if glob(rest, &text[i..], class) {
+                    return true;
+                }
+                if class && c == '/' {
+                    return false;
+                }
+            }
+            glob(rest, "", class)
+        }
+        Some(c) => {
+            text.starts_with(c) && glob(&pattern[c.len_utf8()..], &text[c.len_utf8()..], class)
+        }
+    }
+}
+
+/// The suppressions read from a file, with the current date to decide on expiry.
+#[derive(Debug)]
+pub struct Suppressions {
+    entries: Vec<Suppression>,
+    today: String,
+}
+
+/// The effect of the suppressions on one check.
+#[derive(Debug, Serialize)]
+pub struct Applied<'a> {
+    pub suppressed: usize,
+    //entries that are not expired, but match no finding
+    pub unused: Vec<&'a Suppression>,
+    pub expired: Vec<&'a Suppression>,
+}
+
+impl Suppressions {
+    pub fn read(path: &str) -> Result<Self, error::Error> {
+        info!("Reading suppressions from {path}");
+        let file: SuppressionFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        for (i, entry) in file.suppressions.iter().enumerate() {
+            if entry.consumer.is_none() && entry.dependency.is_none() && entry.member.is_none() {
+                return Err(SuppressionError::MatchesEverything(i).into());
+            }
+            if let Some(expires) = &entry.expires
+                && !is_date(expires)
+            {
+                return Err(SuppressionError::InvalidDate(expires.clone()).into());
+            }
+        }
+        Ok(Suppressions {
+            entries: file.suppressions,
+            today: today(),
+        })
+    }
+
+    /// Removes the suppressed findings from `requirements`, and the classes left without any.
+    pub fn apply(&self, requirements: &mut Vec<ClassRequirements<'_>>) -> Applied<'_> {
+        let (expired, active): (Vec<&Suppression>, Vec<&Suppression>) = self
+            .entries
+            .iter()
+            .partition(|entry| entry.is_expired(&self.today));
+        let mut used = vec![false; active.len()];
+        let mut suppressed = 0;
+        for r in requirements.iter_mut() {
+            let consumer = r.name();
+            suppressed += r.suppress(|dependency, member, _| {
+                //every matching entry is in use, not only the first one
+                let mut matching = false;
+                for (entry, used) in active.iter().zip(used.iter_mut()) {
+                    if entry.matches(consumer, dependency, member) {
+                        *used = true;
+                        matching = true;
+                    }
+                }
+                matching
+            });
+        }
+        requirements.retain(|r| !r.is_empty());
+
+        info!("Suppressed {suppressed} findings");
+        let unused: Vec<&Suppression> = active
+            .into_iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(entry, _)| entry)
+            .collect();
+        for entry in &unused {
+            warn!("Suppression matches no finding: {entry}");
+        }
+        for entry in &expired {
+            warn!("Suppression expired: {entry}");
+        }
+        Applied {
+            suppressed,
+            unused,
+            expired,
+        }
+    }
+}
+
+fn is_date(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+        && (1..=12).contains(&text[5..7].parse::<u8>().unwrap_or(0))
+        && (1..=31).contains(&text[8..10].parse::<u8>().unwrap_or(0))
+}
+
+/// The current UTC date as YYYY-MM-DD.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400) as i64;
+    //civil_from_days by Howard Hinnant
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
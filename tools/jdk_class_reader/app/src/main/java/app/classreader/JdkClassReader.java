@@ -25,6 +25,12 @@ public class JdkClassReader {
 
     public static final String SEPARATOR = ":";
 
+    private static final String STATIC = "S";
+
+    private static final String INSTANCE = "I";
+
+    private static final String ABSTRACT = "A";
+
     private static final ModuleLayer BOOT = ModuleLayer.boot();
 
     public static void main(String[] args) {
@@ -68,6 +74,9 @@ public class JdkClassReader {
             final List<String> methods = stream(clazz.getDeclaredMethods())
                     .filter(method -> Modifier.isPublic(method.getModifiers()) || Modifier.isProtected(method.getModifiers()))
                     .map(m -> String.format("--%s%n", getInternalRepresentation(m, possiblyPolymorphicMethod))).collect(Collectors.toList());
+            final List<String> fields = stream(clazz.getDeclaredFields())
+                    .filter(field -> Modifier.isPublic(field.getModifiers()) || Modifier.isProtected(field.getModifiers()))
+                    .map(f -> String.format("==%s%s%s%n", f.getName(), SEPARATOR, mapType(f.getType()))).collect(Collectors.toList());
             writer.write(className);
             writer.write(SEPARATOR);
             if(isInterface) {
@@ -80,6 +89,7 @@ public class JdkClassReader {
             writer.write(SEPARATOR);
             writer.write(stream(interfaces).map(c -> c.getName().replace('.', '/')).collect(Collectors.joining(",")));
             writer.write(SEPARATOR + (constructors.size() + methods.size()));
+            writer.write(SEPARATOR + fields.size());
             writer.write(System.lineSeparator());
             for (final String constructor : constructors) {
                 writer.write(constructor);
@@ -87,6 +97,9 @@ public class JdkClassReader {
             for (final String method : methods) {
                 writer.write(method);
             }
+            for (final String field : fields) {
+                writer.write(field);
+            }
         }
     }
 
@@ -131,9 +144,11 @@ public class JdkClassReader {
                 .map(JdkClassReader::mapType)
                 .collect(Collectors.joining());
         final String returnType = executable instanceof Method ? mapType(((Method) executable).getReturnType()) : mapType(void.class);
-        final String signature = String.format("%s(%s)%s", name, parameters, returnType);
+        final String flag = Modifier.isStatic(executable.getModifiers()) ? STATIC
+                : Modifier.isAbstract(executable.getModifiers()) ? ABSTRACT : INSTANCE;
+        final String signature = String.format("%s(%s)%s%s%s", name, parameters, returnType, SEPARATOR, flag);
         if(possiblyPolymorphic && executable instanceof Method && hasPolymorphicSignature((Method) executable)) {
-            return signature + ":PS";
+            return signature + SEPARATOR + "PS";
         }
         return signature;
     }
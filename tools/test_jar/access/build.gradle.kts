@@ -0,0 +1,29 @@
+/*
+ * Builds access.jar, whose classes are compiled against the v1 source set but packaged with
+ * the v2 source set, in which classes and members became less accessible.
+ */
+
+plugins {
+    `java-library`
+}
+
+repositories {
+    mavenCentral()
+}
+
+val v1: SourceSet by sourceSets.creating
+val v2: SourceSet by sourceSets.creating
+
+dependencies {
+    compileOnly(v1.output)
+}
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17)
+    }
+}
+
+tasks.jar {
+    from(v2.output)
+}
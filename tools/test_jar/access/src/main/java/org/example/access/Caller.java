@@ -0,0 +1,23 @@
+package org.example.access;
+
+import org.example.access.lib.Hidden;
+import org.example.access.lib.Library;
+
+public class Caller {
+    private int calls;
+
+    public int call() {
+        Library library = new Library();
+        library.open();
+        library.tightened();
+        library.hook();
+        Hidden.run();
+        return library.counter + new Inner().next();
+    }
+
+    class Inner {
+        int next() {
+            return ++calls;
+        }
+    }
+}
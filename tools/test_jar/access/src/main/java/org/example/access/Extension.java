@@ -0,0 +1,10 @@
+package org.example.access;
+
+import org.example.access.lib.Library;
+
+public class Extension extends Library {
+    public void call() {
+        hook();
+        staticHook();
+    }
+}
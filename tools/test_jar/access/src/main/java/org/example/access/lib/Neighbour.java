@@ -0,0 +1,8 @@
+package org.example.access.lib;
+
+public class Neighbour {
+    public int call(Library library) {
+        Hidden.run();
+        return library.counter;
+    }
+}
@@ -0,0 +1,20 @@
+package org.example.access.lib;
+
+public class Library {
+    public int counter;
+
+    public Library() {
+    }
+
+    public void open() {
+    }
+
+    public void tightened() {
+    }
+
+    public void hook() {
+    }
+
+    protected static void staticHook() {
+    }
+}
@@ -0,0 +1,6 @@
+package org.example.access.lib;
+
+class Hidden {
+    public static void run() {
+    }
+}
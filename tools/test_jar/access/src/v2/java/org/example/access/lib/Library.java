@@ -0,0 +1,20 @@
+package org.example.access.lib;
+
+public class Library {
+    int counter;
+
+    protected Library() {
+    }
+
+    public void open() {
+    }
+
+    private void tightened() {
+    }
+
+    protected void hook() {
+    }
+
+    protected static void staticHook() {
+    }
+}
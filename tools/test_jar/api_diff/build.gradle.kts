@@ -0,0 +1,29 @@
+/*
+ * Builds two versions of a library with incompatible API changes: the main source set as
+ * api_diff.jar and the v2 source set as api_diff-v2.jar.
+ */
+
+plugins {
+    `java-library`
+}
+
+repositories {
+    mavenCentral()
+}
+
+val v2: SourceSet by sourceSets.creating
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17)
+    }
+}
+
+val v2Jar by tasks.registering(Jar::class) {
+    archiveClassifier = "v2"
+    from(v2.output)
+}
+
+tasks.named("assemble") {
+    dependsOn(v2Jar)
+}
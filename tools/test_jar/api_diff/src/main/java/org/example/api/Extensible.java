@@ -0,0 +1,6 @@
+package org.example.api;
+
+public class Extensible {
+    public void go() {
+    }
+}
@@ -0,0 +1,6 @@
+package org.example.api;
+
+public abstract class Handler {
+    public void handle() {
+    }
+}
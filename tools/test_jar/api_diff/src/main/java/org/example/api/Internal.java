@@ -0,0 +1,6 @@
+package org.example.api;
+
+class Internal {
+    public void run() {
+    }
+}
@@ -0,0 +1,5 @@
+package org.example.api;
+
+public interface Listener {
+    void onEvent();
+}
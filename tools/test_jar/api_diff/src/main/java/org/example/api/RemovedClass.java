@@ -0,0 +1,6 @@
+package org.example.api;
+
+public class RemovedClass {
+    public void run() {
+    }
+}
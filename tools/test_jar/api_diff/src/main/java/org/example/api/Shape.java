@@ -0,0 +1,7 @@
+package org.example.api;
+
+public class Shape {
+    public double area() {
+        return 0;
+    }
+}
@@ -0,0 +1,34 @@
+package org.example.api;
+
+public class Widget {
+    public String name;
+    public int count;
+    public int limit;
+    protected int level;
+    public static int total;
+
+    public void render() {
+    }
+
+    public int size() {
+        return count;
+    }
+
+    public void resize(int size) {
+        count = size;
+    }
+
+    public void reset() {
+    }
+
+    public static Widget create() {
+        return new Widget();
+    }
+
+    public String label() {
+        return name;
+    }
+
+    private void helper() {
+    }
+}
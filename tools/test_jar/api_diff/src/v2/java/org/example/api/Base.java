@@ -0,0 +1,7 @@
+package org.example.api;
+
+public class Base {
+    public String label() {
+        return "";
+    }
+}
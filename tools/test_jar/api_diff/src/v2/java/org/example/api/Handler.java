@@ -0,0 +1,7 @@
+package org.example.api;
+
+public abstract class Handler {
+    public abstract void handle();
+
+    public abstract void close();
+}
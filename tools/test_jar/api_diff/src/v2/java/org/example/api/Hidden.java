@@ -0,0 +1,4 @@
+package org.example.api;
+
+class Hidden {
+}
@@ -0,0 +1,10 @@
+package org.example.api;
+
+public interface Listener {
+    void onEvent();
+
+    void onError();
+
+    default void onClose() {
+    }
+}
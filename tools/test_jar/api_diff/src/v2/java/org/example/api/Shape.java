@@ -0,0 +1,5 @@
+package org.example.api;
+
+public interface Shape {
+    double area();
+}
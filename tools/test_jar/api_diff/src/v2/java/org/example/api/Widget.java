@@ -0,0 +1,27 @@
+package org.example.api;
+
+public final class Widget extends Base {
+    public long count;
+    public final int limit;
+    int level;
+    public int total;
+
+    public Widget() {
+        limit = 0;
+    }
+
+    public long size() {
+        return count;
+    }
+
+    protected void resize(int size) {
+        count = size;
+    }
+
+    public static void reset() {
+    }
+
+    public Widget create() {
+        return new Widget();
+    }
+}
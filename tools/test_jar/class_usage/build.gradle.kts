@@ -0,0 +1,43 @@
+/*
+ * Builds class_usage.jar, whose classes use the classes of the gone source set in every way
+ * but member references, and by method handles of bootstrap methods. The gone classes are
+ * left out of the JAR, so they are all missing.
+ */
+
+plugins {
+    `java-library`
+}
+
+repositories {
+    mavenCentral()
+}
+
+val gone: SourceSet by sourceSets.creating
+
+dependencies {
+    compileOnly(gone.output)
+}
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17)
+    }
+}
+
+//replaces the invokedynamic of Bootstraps.unused(), the last one followed by areturn, with
+//aconst_null, so its bootstrap method stays in the class without a call site
+tasks.compileJava {
+    doLast {
+        val file = destinationDirectory.file("org/example/usage/Bootstraps.class").get().asFile
+        val bytes = file.readBytes()
+        val index = (bytes.size - 6 downTo 0).first {
+            bytes[it] == 0xba.toByte() && bytes[it + 3] == 0.toByte() &&
+                bytes[it + 4] == 0.toByte() && bytes[it + 5] == 0xb0.toByte()
+        }
+        bytes[index] = 0x01
+        for (i in 1..4) {
+            bytes[index + i] = 0
+        }
+        file.writeBytes(bytes)
+    }
+}
@@ -0,0 +1,9 @@
+package org.example.gone;
+
+public class GoneHandle {
+    public static void used() {
+    }
+
+    public static void unused() {
+    }
+}
@@ -0,0 +1,4 @@
+package org.example.gone;
+
+public class GoneMatrix {
+}
@@ -0,0 +1,14 @@
+package org.example.usage;
+
+import org.example.gone.GoneHandle;
+
+public class Bootstraps {
+    public Runnable used() {
+        return GoneHandle::used;
+    }
+
+    //the build replaces the invokedynamic with aconst_null, leaving its bootstrap method unused
+    public Runnable unused() {
+        return GoneHandle::unused;
+    }
+}
@@ -0,0 +1,6 @@
+package org.example.usage;
+
+import org.example.gone.GoneInterface;
+
+public class Implementation implements GoneInterface {
+}
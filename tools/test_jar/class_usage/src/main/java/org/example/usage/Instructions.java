@@ -0,0 +1,58 @@
+package org.example.usage;
+
+import org.example.gone.GoneArray;
+import org.example.gone.GoneCast;
+import org.example.gone.GoneCheck;
+import org.example.gone.GoneException;
+import org.example.gone.GoneLiteral;
+import org.example.gone.GoneMatrix;
+
+public class Instructions {
+
+    //a tableswitch, a lookupswitch and a wide iinc before the class operands
+    public int jump(int value) {
+        int result;
+        switch (value) {
+            case 0 -> result = 10;
+            case 1 -> result = 11;
+            case 2 -> result = 12;
+            default -> result = 13;
+        }
+        switch (value) {
+            case -100000 -> result += 1;
+            case 5 -> result += 2;
+            case 100000 -> result += 3;
+            default -> result += 4;
+        }
+        result += 1000;
+        return result;
+    }
+
+    public Object cast(Object value) {
+        return (GoneCast) value;
+    }
+
+    public boolean check(Object value) {
+        return value instanceof GoneCheck;
+    }
+
+    public Object[] array() {
+        return new GoneArray[1];
+    }
+
+    public Object[][] matrix() {
+        return new GoneMatrix[2][3];
+    }
+
+    public Class<?> literal() {
+        return GoneLiteral.class;
+    }
+
+    public void handle(Runnable runnable) {
+        try {
+            runnable.run();
+        } catch (GoneException e) {
+            runnable.run();
+        }
+    }
+}
@@ -0,0 +1,6 @@
+package org.example.usage;
+
+import org.example.gone.GoneBase;
+
+public class Subclass extends GoneBase {
+}
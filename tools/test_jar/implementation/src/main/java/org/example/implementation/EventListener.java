@@ -0,0 +1,9 @@
+package org.example.implementation;
+
+import org.example.implementation.lib.Listener;
+
+public class EventListener implements Listener {
+    @Override
+    public void onEvent(String event) {
+    }
+}
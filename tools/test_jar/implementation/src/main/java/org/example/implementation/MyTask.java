@@ -0,0 +1,6 @@
+package org.example.implementation;
+
+import org.example.implementation.lib.Task;
+
+public class MyTask extends Task {
+}
@@ -0,0 +1,10 @@
+package org.example.implementation;
+
+import org.example.implementation.lib.Base;
+
+public class Named extends Base {
+    @Override
+    public String name() {
+        return "named";
+    }
+}
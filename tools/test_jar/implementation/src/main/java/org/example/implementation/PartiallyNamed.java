@@ -0,0 +1,6 @@
+package org.example.implementation;
+
+import org.example.implementation.lib.Base;
+
+public abstract class PartiallyNamed extends Base {
+}
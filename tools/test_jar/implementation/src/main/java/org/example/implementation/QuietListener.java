@@ -0,0 +1,9 @@
+package org.example.implementation;
+
+import org.example.implementation.lib.SpecialListener;
+
+public class QuietListener implements SpecialListener {
+    @Override
+    public void onEvent(String event) {
+    }
+}
@@ -0,0 +1,5 @@
+package org.example.implementation.lib;
+
+public abstract class Base {
+    public abstract String name();
+}
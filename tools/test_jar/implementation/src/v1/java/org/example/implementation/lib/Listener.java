@@ -0,0 +1,5 @@
+package org.example.implementation.lib;
+
+public interface Listener {
+    void onEvent(String event);
+}
@@ -0,0 +1,4 @@
+package org.example.implementation.lib;
+
+public interface SpecialListener extends Listener {
+}
@@ -0,0 +1,7 @@
+package org.example.implementation.lib;
+
+public abstract class Task implements Runnable {
+    @Override
+    public void run() {
+    }
+}
@@ -0,0 +1,7 @@
+package org.example.implementation.lib;
+
+public abstract class Base {
+    public abstract String name();
+
+    public abstract int size();
+}
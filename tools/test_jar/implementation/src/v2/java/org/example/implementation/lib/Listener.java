@@ -0,0 +1,10 @@
+package org.example.implementation.lib;
+
+public interface Listener {
+    void onEvent(String event);
+
+    void onClose();
+
+    default void onOpen() {
+    }
+}
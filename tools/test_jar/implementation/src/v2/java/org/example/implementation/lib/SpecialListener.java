@@ -0,0 +1,7 @@
+package org.example.implementation.lib;
+
+public interface SpecialListener extends Listener {
+    @Override
+    default void onClose() {
+    }
+}
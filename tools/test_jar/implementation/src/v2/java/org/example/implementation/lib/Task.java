@@ -0,0 +1,4 @@
+package org.example.implementation.lib;
+
+public abstract class Task implements Runnable {
+}
@@ -0,0 +1,25 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+package org.example;
+
+import org.apache.commons.math3.util.Precision;
+
+public class LibraryWithFieldDependencies {
+    public static final int SCALE = 1000;
+
+    public static final String UNIT = "ulp";
+
+    public static boolean isNegligible(final double value) {
+        return Math.abs(value) < Precision.EPSILON;
+    }
+
+    public static void printNegligible(final double value) {
+        System.out.println(isNegligible(value));
+    }
+}
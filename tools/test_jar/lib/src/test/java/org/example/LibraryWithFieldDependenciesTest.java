@@ -0,0 +1,22 @@
+/*
+* This Source Code Form is subject to the terms of the
+* Mozilla Public License, v. 2.0. If a copy of the MPL was not distributed
+* with this file, You can obtain one at http://mozilla.org/MPL/2.0/.
+*
+* SPDX-License-Identifier: MPL-2.0
+*/
+
+package org.example;
+
+import org.junit.jupiter.api.Test;
+
+import static org.junit.jupiter.api.Assertions.*;
+
+class LibraryWithFieldDependenciesTest {
+
+    @Test
+    void isNegligible() {
+        assertTrue(LibraryWithFieldDependencies.isNegligible(0d));
+        assertFalse(LibraryWithFieldDependencies.isNegligible(1d));
+    }
+}
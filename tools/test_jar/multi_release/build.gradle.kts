@@ -0,0 +1,41 @@
+/*
+ * Builds a multi-release JAR: the base classes target Java 8, the classes of the
+ * java11 source set are stored under META-INF/versions/11.
+ */
+
+plugins {
+    `java-library`
+}
+
+repositories {
+    mavenCentral()
+}
+
+val java11: SourceSet by sourceSets.creating
+
+dependencies {
+    implementation(libs.commons.math3)
+}
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17)
+    }
+}
+
+tasks.named<JavaCompile>("compileJava") {
+    options.release = 8
+}
+
+tasks.named<JavaCompile>(java11.compileJavaTaskName) {
+    options.release = 11
+}
+
+tasks.named<Jar>("jar") {
+    into("META-INF/versions/11") {
+        from(java11.output)
+    }
+    manifest {
+        attributes("Multi-Release" to "true")
+    }
+}
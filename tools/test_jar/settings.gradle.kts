@@ -11,4 +11,4 @@ plugins {
 }
 
 rootProject.name = "test_jar"
-include("lib")
+include("lib", "multi_release", "api_diff", "class_usage", "static_mismatch", "access", "implementation")
@@ -0,0 +1,34 @@
+/*
+ * Builds static_mismatch.jar, whose caller is compiled against the v1 source set but packaged
+ * with the v2 source set, in which methods became static or stopped being static. The JDK
+ * classes in src/jdk patch java.base, so callers can also use JDK methods the wrong way.
+ */
+
+plugins {
+    `java-library`
+}
+
+repositories {
+    mavenCentral()
+}
+
+val v1: SourceSet by sourceSets.creating
+val v2: SourceSet by sourceSets.creating
+
+dependencies {
+    compileOnly(v1.output)
+}
+
+java {
+    toolchain {
+        languageVersion = JavaLanguageVersion.of(17)
+    }
+}
+
+tasks.compileJava {
+    options.compilerArgs.addAll(listOf("--patch-module", "java.base=${projectDir}/src/jdk/java"))
+}
+
+tasks.jar {
+    from(v2.output)
+}
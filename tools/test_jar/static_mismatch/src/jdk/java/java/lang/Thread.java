@@ -0,0 +1,18 @@
+package java.lang;
+
+public class Thread implements Runnable {
+    public static Thread currentThread() {
+        return null;
+    }
+
+    public boolean isInterrupted() {
+        return false;
+    }
+
+    public boolean interrupted() {
+        return false;
+    }
+
+    public void run() {
+    }
+}
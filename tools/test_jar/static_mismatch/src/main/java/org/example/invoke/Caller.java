@@ -0,0 +1,11 @@
+package org.example.invoke;
+
+import java.util.function.IntUnaryOperator;
+
+public class Caller {
+    public int call(Utility utility, Greeter greeter) {
+        IntUnaryOperator twice = Utility::twice;
+        return twice.applyAsInt(1) + Utility.twice(2) + utility.half(4) + Utility.unchanged(8)
+                + greeter.greet().length();
+    }
+}
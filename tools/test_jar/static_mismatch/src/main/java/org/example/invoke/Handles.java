@@ -0,0 +1,23 @@
+package org.example.invoke;
+
+import java.lang.constant.MethodHandleDesc;
+import java.lang.invoke.MethodHandle;
+import java.lang.invoke.MethodType;
+import java.lang.invoke.VarHandle;
+import java.util.Optional;
+
+public class Handles {
+    public MethodType typeOf(MethodHandle handle) {
+        VarHandle.fullFence();
+        return handle.type();
+    }
+
+    public Object call(MethodHandle handle) throws Throwable {
+        return (Object) handle.invokeExact();
+    }
+
+    //added in Java 12
+    public Optional<MethodHandleDesc> describe(MethodHandle handle) {
+        return handle.describeConstable();
+    }
+}
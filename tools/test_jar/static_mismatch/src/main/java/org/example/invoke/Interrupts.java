@@ -0,0 +1,7 @@
+package org.example.invoke;
+
+public class Interrupts {
+    public boolean clear() {
+        return Thread.currentThread().interrupted();
+    }
+}
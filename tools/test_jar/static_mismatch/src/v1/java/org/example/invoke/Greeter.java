@@ -0,0 +1,7 @@
+package org.example.invoke;
+
+public interface Greeter {
+    default String greet() {
+        return "Hello";
+    }
+}
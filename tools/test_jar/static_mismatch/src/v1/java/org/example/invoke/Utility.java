@@ -0,0 +1,15 @@
+package org.example.invoke;
+
+public class Utility {
+    public static int twice(int value) {
+        return value * 2;
+    }
+
+    public int half(int value) {
+        return value / 2;
+    }
+
+    public static int unchanged(int value) {
+        return value;
+    }
+}
@@ -0,0 +1,7 @@
+package org.example.invoke;
+
+public interface Greeter {
+    static String greet() {
+        return "Hello";
+    }
+}
@@ -0,0 +1,15 @@
+package org.example.invoke;
+
+public class Utility {
+    public int twice(int value) {
+        return value * 2;
+    }
+
+    public static int half(int value) {
+        return value / 2;
+    }
+
+    public static int unchanged(int value) {
+        return value;
+    }
+}